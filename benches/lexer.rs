@@ -0,0 +1,62 @@
+// throughput of turning source into tokens, without the parser
+//
+// `cargo bench --bench lexer`, the time per byte should stay the same as the sources get bigger
+
+use std::{
+	hint::black_box,
+	time::{Duration, Instant},
+};
+
+const RUNS: u32 = 5;
+
+fn main() {
+	for functions in [1_000, 4_000, 16_000] {
+		let source = generate(functions);
+
+		let mut best = Duration::MAX;
+
+		for _ in 0..RUNS {
+			let start = Instant::now();
+			black_box(asdf::lex(black_box(&source)).unwrap());
+
+			best = best.min(start.elapsed());
+		}
+
+		let megabytes = source.len() as f64 / (1024.0 * 1024.0);
+
+		println!(
+			"{:>8} bytes  {:>10.3?}  {:>8.2} MB/s",
+			source.len(),
+			best,
+			megabytes / best.as_secs_f64()
+		);
+	}
+}
+
+// the kind of code the generated sources are made of
+fn generate(functions: usize) -> String {
+	let mut ret = String::new();
+
+	for i in 0..functions {
+		ret += &format!(
+			"fn function_{i}(a: i64, b: i64) -> i64 {{
+	mut x = a * {i} + b;
+	mut name = \"function_{i}\\n\";
+
+	while x > 100 {{
+		x = x / 2;
+	}}
+
+	if x == {i} && b != 0 {{
+		return x - 1;
+	}}
+
+	return x + name[0];
+}}
+
+"
+		);
+	}
+
+	return ret;
+}
@@ -0,0 +1,19 @@
+// `cargo fuzz run check`, only goes as far as type checking and the lints, so it gets through
+// more inputs than `parse`
+
+#![no_main]
+
+use asdf::instance::Instance;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+	let Ok(source) = std::str::from_utf8(data) else {
+		return;
+	};
+
+	let mut instance = Instance::without_stdlib();
+
+	if instance.add_source("main.asdf", source).is_ok() {
+		let _ = instance.check();
+	}
+});
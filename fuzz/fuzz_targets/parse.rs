@@ -0,0 +1,15 @@
+// `cargo fuzz run parse`, compiles the input along with the standard library without running it
+
+#![no_main]
+
+use asdf::instance::Instance;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+	let Ok(source) = std::str::from_utf8(data) else {
+		return;
+	};
+
+	// errors are expected, only panics and crashes are bugs
+	let _ = Instance::new().parse(source);
+});
@@ -0,0 +1,50 @@
+use crate::asdf::{ast, byte_code, lint, location::Location, tc, Code, Diagnostic, Result};
+
+#[derive(Debug, Clone)]
+pub struct AddressOf {
+	node: Box<ast::BoxedNode>,
+	location: Location,
+}
+
+impl AddressOf {
+	pub fn new(node: ast::BoxedNode, location: Location) -> Self {
+		return Self {
+			node: Box::new(node),
+			location,
+		};
+	}
+}
+
+impl ast::Node for AddressOf {
+	fn get_location(&self) -> &Location {
+		return &self.location;
+	}
+
+	fn emit(&self, ctx: &mut byte_code::Context) {
+		// the reference already is the address
+		self.node.emit(ctx);
+	}
+
+	fn pre_type_check(&self, ctx: &mut tc::Context) {
+		self.node.pre_type_check(ctx);
+	}
+
+	fn type_check(&mut self, ctx: &mut tc::Context) -> Result<tc::Type> {
+		let tc::Type::Reference(typ) = self.node.type_check(ctx)? else {
+			return Err(Diagnostic::error(
+				Code::NotAddressable,
+				"cannot take the address of a value",
+			)
+			.into());
+		};
+
+		return Ok(tc::Type::Pointer(typ));
+	}
+
+	fn lint(&self, ctx: &mut lint::Context) {
+		match self.node.as_ref() {
+			ast::BoxedNode::Stack(x) => ctx.borrow(x.get_offset()),
+			x => x.lint(ctx),
+		}
+	}
+}
@@ -1,21 +1,48 @@
-use crate::asdf::{ast, byte_code, operators::Operators, tc, Result};
+use crate::asdf::{
+	ast, byte_code, lint, location::Location, operators::Operators, runtime, tc, Code, Diagnostic,
+	Result,
+};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BinaryOperation {
 	operator: Operators,
 	lhs: Box<ast::BoxedNode>,
 	rhs: Box<ast::BoxedNode>,
+	operand_type: tc::Type,
+	location: Location,
 }
 
 impl BinaryOperation {
-	pub fn new(operator: Operators, lhs: ast::BoxedNode, rhs: ast::BoxedNode) -> Self {
+	pub fn new(
+		operator: Operators,
+		lhs: ast::BoxedNode,
+		rhs: ast::BoxedNode,
+		location: Location,
+	) -> Self {
 		return Self {
 			operator,
 			lhs: Box::new(lhs),
 			rhs: Box::new(rhs),
+			operand_type: tc::Type::None,
+			location,
 		};
 	}
 
+	fn str_operation(&self, ctx: &mut byte_code::Context) {
+		let function = match self.operator {
+			Operators::Addition => runtime::str_concat as u64,
+			Operators::Equals | Operators::NotEquals => runtime::str_equals as u64,
+			_ => unreachable!(),
+		};
+
+		ast::CallStatement::emit_runtime_call(ctx, function, &[&self.lhs, &self.rhs]);
+
+		if let Operators::NotEquals = self.operator {
+			// xor rax, 1
+			ctx.emit(&[0x48, 0x83, 0xF0, 0x01]);
+		}
+	}
+
 	fn assignment(&self, ctx: &mut byte_code::Context) {
 		// push rbx
 		ctx.emit(&[0x53]);
@@ -28,8 +55,12 @@ impl BinaryOperation {
 
 		self.rhs.emit(ctx);
 
-		// mov [rbx], rax
-		ctx.emit(&[0x48, 0x89, 0x03]);
+		match self.lhs.as_ref() {
+			// mov [rbx], al
+			ast::BoxedNode::Indirection(x) if x.points_to_char() => ctx.emit(&[0x88, 0x03]),
+			// mov [rbx], rax
+			_ => ctx.emit(&[0x48, 0x89, 0x03]),
+		};
 
 		// pop rbx
 		ctx.emit(&[0x5B]);
@@ -167,7 +198,7 @@ impl BinaryOperation {
 		ctx.pop(0x8);
 	}
 
-	fn equals(&self, ctx: &mut byte_code::Context) {
+	fn comparison(&self, ctx: &mut byte_code::Context, condition: u8) {
 		// push rbx
 		ctx.emit(&[0x53]);
 		ctx.push(0x8);
@@ -182,19 +213,47 @@ impl BinaryOperation {
 		// cmp rax, rbx
 		ctx.emit(&[0x48, 0x39, 0xD8]);
 
-		// sete al
-		ctx.emit(&[0x0F, 0x94, 0xC0]);
+		// setcc al
+		ctx.emit(&[0x0F, condition, 0xC0]);
+
+		// movzx rax, al
+		ctx.emit(&[0x48, 0x0F, 0xB6, 0xC0]);
 
 		// pop rbx
 		ctx.emit(&[0x5B]);
 		ctx.pop(0x8);
 	}
 
+	fn equals(&self, ctx: &mut byte_code::Context) {
+		// sete
+		self.comparison(ctx, 0x94);
+	}
+
 	fn not_equals(&self, ctx: &mut byte_code::Context) {
-		self.equals(ctx);
+		// setne
+		self.comparison(ctx, 0x95);
+	}
+
+	// the flags are set by `cmp rhs, lhs`, so the conditions are mirrored
+
+	fn less_than(&self, ctx: &mut byte_code::Context) {
+		// setg
+		self.comparison(ctx, 0x9F);
+	}
 
-		// xor al, 1
-		ctx.emit(&[0x34, 0x01]);
+	fn greater_than(&self, ctx: &mut byte_code::Context) {
+		// setl
+		self.comparison(ctx, 0x9C);
+	}
+
+	fn less_than_or_equal(&self, ctx: &mut byte_code::Context) {
+		// setge
+		self.comparison(ctx, 0x9D);
+	}
+
+	fn greater_than_or_equal(&self, ctx: &mut byte_code::Context) {
+		// setle
+		self.comparison(ctx, 0x9E);
 	}
 
 	fn logical_and(&self, ctx: &mut byte_code::Context) {
@@ -261,7 +320,15 @@ impl BinaryOperation {
 }
 
 impl ast::Node for BinaryOperation {
+	fn get_location(&self) -> &Location {
+		return &self.location;
+	}
+
 	fn emit(&self, ctx: &mut byte_code::Context) {
+		if self.operand_type == tc::Type::Str && !matches!(self.operator, Operators::Assignment) {
+			return self.str_operation(ctx);
+		}
+
 		match self.operator {
 			Operators::Assignment => self.assignment(ctx),
 			Operators::Addition => self.addition(ctx),
@@ -271,6 +338,10 @@ impl ast::Node for BinaryOperation {
 			Operators::Modulo => self.modulo(ctx),
 			Operators::Equals => self.equals(ctx),
 			Operators::NotEquals => self.not_equals(ctx),
+			Operators::LessThan => self.less_than(ctx),
+			Operators::GreaterThan => self.greater_than(ctx),
+			Operators::LessThanOrEqual => self.less_than_or_equal(ctx),
+			Operators::GreaterThanOrEqual => self.greater_than_or_equal(ctx),
 			Operators::LogicalAnd => self.logical_and(ctx),
 			Operators::LogicalOr => self.logical_or(ctx),
 		}
@@ -281,26 +352,86 @@ impl ast::Node for BinaryOperation {
 		self.rhs.pre_type_check(ctx);
 	}
 
-	fn type_check(&self, ctx: &mut tc::Context) -> Result<tc::Type> {
+	fn type_check(&mut self, ctx: &mut tc::Context) -> Result<tc::Type> {
 		let mut lhs = self.lhs.type_check(ctx)?;
 		let rhs = self.rhs.type_check(ctx)?;
 
 		if let Operators::Assignment = self.operator {
 			let tc::Type::Reference(ptr_type) = lhs else {
-				return Err(format!(""));
+				return Err(Diagnostic::error(
+					Code::NotAssignable,
+					format!("cannot assign to a value of type '{}'", lhs),
+				)
+				.with_note("only variables and dereferenced pointers can be assigned to")
+				.into());
 			};
 
 			lhs = *ptr_type;
+
+			// like the result of another assignment, there's nothing to store
+			if ctx.resolve(&rhs) == tc::Type::None {
+				let message = match self.lhs.as_ref() {
+					ast::BoxedNode::Stack(x) => {
+						format!("'{}' can't hold a value of type '()'", x.get_name())
+					}
+					_ => format!("cannot assign a value of type '()'"),
+				};
+
+				return Err(Diagnostic::error(Code::TypeMismatch, message).into());
+			}
 		}
 
-		if lhs != rhs {
-			return Err(format!(""));
+		ctx.unify(&lhs, &rhs)?;
+
+		let is_assignment = matches!(self.operator, Operators::Assignment);
+
+		// operands nothing else pinned down are taken to be integers
+		if !is_assignment && !ctx.resolve(&lhs).is_resolved() {
+			ctx.unify(&tc::Type::I64, &lhs)?;
 		}
 
-		if let Operators::Assignment = self.operator {
-			return Ok(tc::Type::None);
+		self.operand_type = ctx.resolve(&lhs);
+
+		if self.operand_type == tc::Type::Str {
+			match self.operator {
+				Operators::Assignment
+				| Operators::Addition
+				| Operators::Equals
+				| Operators::NotEquals => {}
+				_ => {
+					return Err(Diagnostic::error(
+						Code::InvalidOperator,
+						format!(
+							"cannot apply '{:?}' to '{}'",
+							self.operator, self.operand_type
+						),
+					)
+					.into())
+				}
+			}
 		}
 
-		return Ok(lhs);
+		return Ok(match self.operator {
+			Operators::Assignment => tc::Type::None,
+			Operators::Equals
+			| Operators::NotEquals
+			| Operators::LessThan
+			| Operators::GreaterThan
+			| Operators::LessThanOrEqual
+			| Operators::GreaterThanOrEqual => tc::Type::I64,
+			_ => lhs,
+		});
+	}
+
+	fn lint(&self, ctx: &mut lint::Context) {
+		self.rhs.lint(ctx);
+
+		// the value is assigned after the right hand side is evaluated
+		match (&self.operator, self.lhs.as_ref()) {
+			(Operators::Assignment, ast::BoxedNode::Stack(x)) => {
+				ctx.write(x.get_offset(), x.get_name(), ast::Node::get_location(x))
+			}
+			(_, x) => x.lint(ctx),
+		}
 	}
 }
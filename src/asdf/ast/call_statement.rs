@@ -1,43 +1,205 @@
-use crate::asdf::{ast, byte_code, tc, Result};
+use std::collections::HashMap;
 
-#[derive(Debug)]
+use crate::asdf::{ast, byte_code, lint, location::Location, tc, Code, Diagnostic, Result};
+
+// rdi, rsi, rdx, rcx, r8 and r9, arguments aren't passed on the stack yet
+pub const ARGUMENT_REGISTERS: usize = 6;
+
+#[derive(Debug, Clone)]
 pub struct CallStatement {
 	function: Box<ast::BoxedNode>,
 	arguments: Vec<ast::BoxedNode>,
+	location: Location,
 }
 
 impl CallStatement {
-	pub fn new(function: ast::BoxedNode, arguments: Vec<ast::BoxedNode>) -> Self {
+	pub fn new(
+		function: ast::BoxedNode,
+		arguments: Vec<ast::BoxedNode>,
+		location: Location,
+	) -> Self {
 		return Self {
 			function: Box::new(function),
 			arguments,
+			location,
 		};
 	}
-}
 
-impl ast::Node for CallStatement {
-	fn emit(&self, ctx: &mut byte_code::Context) {
-		assert!(self.arguments.len() <= 2);
+	pub fn set_function(&mut self, function: ast::BoxedNode) {
+		*self.function = function;
+	}
 
-		// push rdi
-		ctx.emit(&[0x57]);
-		ctx.push(0x8);
+	pub fn get_arguments_mut(&mut self) -> &mut Vec<ast::BoxedNode> {
+		return &mut self.arguments;
+	}
 
-		for (i, node) in self.arguments.iter().enumerate() {
-			// mov rax, argument
+	// a method call has already checked its receiver, so the first argument takes that type
+	// instead of being checked again
+	pub fn type_check_with_receiver(
+		&mut self,
+		ctx: &mut tc::Context,
+		receiver: Option<tc::Type>,
+	) -> Result<tc::Type> {
+		let function = self.function.type_check(ctx)?;
+
+		let tc::Type::Function(ret, args) = ctx.resolve(&function) else {
+			return Err(Diagnostic::error(
+				Code::NotCallable,
+				format!("cannot call a value of type '{}'", ctx.resolve(&function)),
+			)
+			.into());
+		};
+
+		// checked after the function, which resolves the name it refers to
+		let type_parameters = match self.function.as_ref() {
+			ast::BoxedNode::Relative(x) => ctx.find_type_parameters(x.get_function_name()),
+			_ => None,
+		};
+
+		if self.arguments.len() != args.len() {
+			let mut diagnostic = Diagnostic::error(
+				Code::ArgumentCount,
+				format!(
+					"expected {} arguments, got {}",
+					args.len(),
+					self.arguments.len()
+				),
+			)
+			.with_note(format!("the function has the type '{}'", function));
+
+			let definition = match self.function.as_ref() {
+				ast::BoxedNode::Relative(x) => ctx.find_function_location(x.get_function_name()),
+				_ => None,
+			};
+
+			if let Some(location) = definition {
+				diagnostic = diagnostic.with_secondary(location.clone(), "defined here");
+			}
+
+			return Err(diagnostic.into());
+		}
+
+		if args.len() > ARGUMENT_REGISTERS {
+			return Err(Diagnostic::error(
+				Code::TooManyArguments,
+				format!(
+					"cannot call a function with more than {} arguments",
+					ARGUMENT_REGISTERS
+				),
+			)
+			.into());
+		}
+
+		// every type parameter gets a fresh variable, which the arguments then pin down
+		let type_arguments: HashMap<_, _> = type_parameters
+			.iter()
+			.flatten()
+			.map(|x| (x.clone(), ctx.new_variable()))
+			.collect();
+
+		for (i, node) in self.arguments.iter_mut().enumerate() {
+			let typ = match (i, &receiver) {
+				(0, Some(x)) => x.clone(),
+				_ => node.type_check(ctx)?,
+			};
+
+			ctx.unify(&args[i].substitute(&type_arguments), &typ)?;
+		}
+
+		if let (Some(type_parameters), ast::BoxedNode::Relative(relative)) =
+			(type_parameters, self.function.as_mut())
+		{
+			let function_name = relative.get_function_name().to_string();
+			let mut types = vec![];
+
+			for i in type_parameters {
+				let typ = ctx.resolve(&type_arguments[&i]);
+
+				if !typ.is_resolved() {
+					return Err(Diagnostic::error(
+						Code::CannotInfer,
+						format!("cannot infer type parameter '{}' of '{}'", i, function_name),
+					)
+					.into());
+				}
+
+				// the instance would have a parameter that holds nothing
+				if typ == tc::Type::None {
+					return Err(Diagnostic::error(
+						Code::TypeMismatch,
+						format!(
+							"type parameter '{}' of '{}' can't be '()'",
+							i, function_name
+						),
+					)
+					.into());
+				}
+
+				types.push(typ);
+			}
+
+			relative.set_instance(ctx.instantiate(&function_name, types));
+		}
+
+		// every type parameter is resolved by now, so the caller gets a concrete type
+		return Ok(ctx.resolve(&ret.substitute(&type_arguments)));
+	}
+
+	// calls straight into a runtime helper, for operations that aren't done inline
+	pub fn emit_runtime_call(
+		ctx: &mut byte_code::Context,
+		address: u64,
+		arguments: &[&ast::BoxedNode],
+	) {
+		Self::emit_call(ctx, arguments, |ctx| {
+			// mov rax, address
+			ctx.emit(
+				[[0x48, 0xB8].to_vec(), address.to_ne_bytes().into()]
+					.concat()
+					.as_slice(),
+			);
+		});
+	}
+
+	fn emit_call(
+		ctx: &mut byte_code::Context,
+		arguments: &[&ast::BoxedNode],
+		function: impl FnOnce(&mut byte_code::Context),
+	) {
+		assert!(arguments.len() <= ARGUMENT_REGISTERS);
+
+		// every argument is kept on the stack until all of them are evaluated, as evaluating one
+		// can call into another function and clobber the registers
+		for node in arguments {
 			node.emit(ctx);
 
+			// push rax
+			ctx.emit(&[0x50]);
+			ctx.push(0x8);
+		}
+
+		for i in (0..arguments.len()).rev() {
 			match i {
-				// mov rdi, rax
-				0 => ctx.emit(&[0x48, 0x89, 0xC7]),
-				// mov rsi, rax
-				1 => ctx.emit(&[0x48, 0x89, 0xC6]),
+				// pop rdi
+				0 => ctx.emit(&[0x5F]),
+				// pop rsi
+				1 => ctx.emit(&[0x5E]),
+				// pop rdx
+				2 => ctx.emit(&[0x5A]),
+				// pop rcx
+				3 => ctx.emit(&[0x59]),
+				// pop r8
+				4 => ctx.emit(&[0x41, 0x58]),
+				// pop r9
+				5 => ctx.emit(&[0x41, 0x59]),
 				_ => unreachable!(),
 			};
+
+			ctx.pop(0x8);
 		}
 
 		// mov rax, function
-		self.function.emit(ctx);
+		function(ctx);
 
 		// TODO: this is a temporary alignment for rsp, needs cleaning up
 		let bytes_to_align = 0x10 - (ctx.get_stack_size() % 0x10) as u8;
@@ -50,10 +212,17 @@ impl ast::Node for CallStatement {
 
 		// add rsp, bytes_to_align
 		ctx.emit(&[0x48, 0x83, 0xC4, bytes_to_align]);
+	}
+}
+
+impl ast::Node for CallStatement {
+	fn get_location(&self) -> &Location {
+		return &self.location;
+	}
 
-		// pop rdi
-		ctx.emit(&[0x5F]);
-		ctx.pop(0x8);
+	fn emit(&self, ctx: &mut byte_code::Context) {
+		let arguments = self.arguments.iter().collect::<Vec<_>>();
+		Self::emit_call(ctx, &arguments, |ctx| self.function.emit(ctx));
 	}
 
 	fn pre_type_check(&self, ctx: &mut tc::Context) {
@@ -64,21 +233,15 @@ impl ast::Node for CallStatement {
 		}
 	}
 
-	fn type_check(&self, ctx: &mut tc::Context) -> Result<tc::Type> {
-		let tc::Type::Function(ret, args) = self.function.type_check(ctx)? else {
-			return Err(format!(""));
-		};
+	fn type_check(&mut self, ctx: &mut tc::Context) -> Result<tc::Type> {
+		return self.type_check_with_receiver(ctx, None);
+	}
 
-		if self.arguments.len() != args.len() {
-			return Err(format!(""));
-		}
+	fn lint(&self, ctx: &mut lint::Context) {
+		self.function.lint(ctx);
 
-		for (i, node) in self.arguments.iter().enumerate() {
-			if node.type_check(ctx)? != args[i] {
-				return Err(format!(""));
-			}
+		for i in &self.arguments {
+			i.lint(ctx);
 		}
-
-		return Ok(*ret);
 	}
 }
@@ -1,35 +1,61 @@
-use crate::asdf::{ast, byte_code, tc, Result};
+use crate::asdf::{ast, byte_code, lint, location::Location, tc, Code, Diagnostic, Result};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Dereference {
 	node: Box<ast::BoxedNode>,
+	location: Location,
 }
 
 impl Dereference {
-	pub fn new(node: ast::BoxedNode) -> Self {
+	pub fn new(node: ast::BoxedNode, location: Location) -> Self {
 		return Self {
 			node: Box::new(node),
+			location,
 		};
 	}
+
+	pub fn get_node(&self) -> &ast::BoxedNode {
+		return &self.node;
+	}
 }
 
 impl ast::Node for Dereference {
+	fn get_location(&self) -> &Location {
+		return &self.location;
+	}
+
 	fn emit(&self, ctx: &mut byte_code::Context) {
 		self.node.emit(ctx);
 
-		// mov rax, [rax]
-		ctx.emit(&[0x48, 0x8B, 0x00]);
+		match self.node.as_ref() {
+			// movzx rax, byte [rax]
+			ast::BoxedNode::Indirection(x) if x.points_to_char() => {
+				ctx.emit(&[0x48, 0x0F, 0xB6, 0x00])
+			}
+			// mov rax, [rax]
+			_ => ctx.emit(&[0x48, 0x8B, 0x00]),
+		};
 	}
 
 	fn pre_type_check(&self, ctx: &mut tc::Context) {
 		self.node.pre_type_check(ctx);
 	}
 
-	fn type_check(&self, ctx: &mut tc::Context) -> Result<tc::Type> {
-		let tc::Type::Reference(typ) = self.node.type_check(ctx)? else {
-			return Err(format!(""));
+	fn type_check(&mut self, ctx: &mut tc::Context) -> Result<tc::Type> {
+		let value = self.node.type_check(ctx)?;
+
+		let tc::Type::Reference(typ) = value else {
+			return Err(Diagnostic::error(
+				Code::NotDereferenceable,
+				format!("cannot dereference '{}'", value),
+			)
+			.into());
 		};
 
 		return Ok(*typ);
 	}
+
+	fn lint(&self, ctx: &mut lint::Context) {
+		self.node.lint(ctx);
+	}
 }
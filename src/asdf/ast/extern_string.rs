@@ -1,20 +1,24 @@
-use crate::asdf::{ast, byte_code, tc, Result};
+use crate::asdf::{ast, byte_code, location::Location, tc, Result};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ExternString {
 	value: String,
+	location: Location,
 }
 
 impl ExternString {
-	pub fn new(value: String) -> Self {
-		return Self { value };
+	pub fn new(value: String, location: Location) -> Self {
+		return Self { value, location };
 	}
 }
 
 impl ast::Node for ExternString {
+	fn get_location(&self) -> &Location {
+		return &self.location;
+	}
+
 	fn emit(&self, ctx: &mut byte_code::Context) {
-		// TODO: make an actual string struct that frees memory
-		let ptr = Box::leak(Box::new(self.value.clone() + "\x00")).as_ptr() as u64;
+		let ptr = ctx.string_literal(&self.value);
 
 		// mov rax, ptr
 		ctx.emit(
@@ -24,7 +28,7 @@ impl ast::Node for ExternString {
 		);
 	}
 
-	fn type_check(&self, _: &mut tc::Context) -> Result<tc::Type> {
-		return Ok(tc::Type::Pointer(Box::new(tc::Type::Char)));
+	fn type_check(&mut self, _: &mut tc::Context) -> Result<tc::Type> {
+		return Ok(tc::Type::Str);
 	}
 }
@@ -1,34 +1,139 @@
-use crate::asdf::{ast, byte_code, tc, Result};
+use std::collections::HashMap;
 
-#[derive(Debug)]
+use crate::asdf::{ast, byte_code, lint, location::Location, tc, Code, Diagnostic, Result};
+
+#[derive(Debug, Clone)]
 pub struct Function {
 	name: String,
+	type_parameters: Vec<String>,
+	type_arguments: HashMap<String, tc::Type>,
 	return_type: tc::Type,
 	params: Vec<tc::Type>,
+	variables: Vec<(String, tc::Type)>,
 	nodes: Vec<ast::BoxedNode>,
 	stack_size: u32,
+	module_name: Option<String>,
+	is_public: bool,
+	// the lints `#[allow(...)]` silences in this function
+	allowed_lints: Vec<String>,
+	// the `///` comments in front of it
+	doc: Option<String>,
+	location: Location,
 }
 
 impl Function {
+	#[allow(clippy::too_many_arguments)]
 	pub fn new(
 		name: String,
+		type_parameters: Vec<String>,
 		return_type: tc::Type,
 		params: Vec<tc::Type>,
+		variables: Vec<String>,
 		nodes: Vec<ast::BoxedNode>,
 		stack_size: u32,
+		location: Location,
 	) -> Self {
+		let variables = variables
+			.into_iter()
+			.enumerate()
+			.map(|(i, x)| (x, tc::Type::Variable(i)))
+			.collect();
+
 		return Self {
 			name,
+			type_parameters,
+			type_arguments: HashMap::new(),
 			return_type,
 			params,
+			variables,
 			nodes,
 			stack_size,
+			module_name: None,
+			is_public: true,
+			allowed_lints: vec![],
+			doc: None,
+			location,
+		};
+	}
+
+	// the return type is inferred from the return statements instead
+	pub fn infer_return_type(&mut self) {
+		self.return_type = tc::Type::Variable(self.variables.len());
+		self.variables
+			.push(("return".into(), self.return_type.clone()));
+	}
+
+	pub fn set_module(&mut self, module_name: String, is_public: bool) {
+		self.module_name = Some(module_name);
+		self.is_public = is_public;
+	}
+
+	pub fn set_allowed_lints(&mut self, allowed_lints: Vec<String>) {
+		self.allowed_lints = allowed_lints;
+	}
+
+	pub fn set_doc(&mut self, doc: String) {
+		self.doc = Some(doc);
+	}
+
+	pub fn get_doc(&self) -> Option<&str> {
+		return self.doc.as_deref();
+	}
+
+	pub fn get_name(&self) -> &str {
+		return &self.name;
+	}
+
+	pub fn set_name(&mut self, name: String) {
+		self.name = name;
+	}
+
+	pub fn get_type(&self) -> tc::Type {
+		return tc::Type::Function(Box::new(self.return_type.clone()), self.params.clone());
+	}
+
+	pub fn get_type_parameters(&self) -> &[String] {
+		return &self.type_parameters;
+	}
+
+	pub fn instantiate(&self, name: String, type_arguments: HashMap<String, tc::Type>) -> Self {
+		return Self {
+			name,
+			type_parameters: vec![],
+			return_type: self.return_type.substitute(&type_arguments),
+			params: self
+				.params
+				.iter()
+				.map(|x| x.substitute(&type_arguments))
+				.collect(),
+			type_arguments,
+			variables: self.variables.clone(),
+			nodes: self.nodes.clone(),
+			stack_size: self.stack_size,
+			module_name: self.module_name.clone(),
+			is_public: self.is_public,
+			allowed_lints: self.allowed_lints.clone(),
+			doc: self.doc.clone(),
+			location: self.location.clone(),
 		};
 	}
+
+	fn is_generic(&self) -> bool {
+		return !self.type_parameters.is_empty();
+	}
 }
 
 impl ast::Node for Function {
+	fn get_location(&self) -> &Location {
+		return &self.location;
+	}
+
 	fn emit(&self, ctx: &mut byte_code::Context) {
+		// only the instances of a generic function get emitted
+		if self.is_generic() {
+			return;
+		}
+
 		ctx.define_function(self.name.clone(), ctx.get_current_position());
 
 		// push rbp
@@ -55,6 +160,14 @@ impl ast::Node for Function {
 				0 => ctx.emit(&[0x48, 0x89, 0xF8]),
 				// mov rax, rsi
 				1 => ctx.emit(&[0x48, 0x89, 0xF0]),
+				// mov rax, rdx
+				2 => ctx.emit(&[0x48, 0x89, 0xD0]),
+				// mov rax, rcx
+				3 => ctx.emit(&[0x48, 0x89, 0xC8]),
+				// mov rax, r8
+				4 => ctx.emit(&[0x4C, 0x89, 0xC0]),
+				// mov rax, r9
+				5 => ctx.emit(&[0x4C, 0x89, 0xC8]),
 				_ => unreachable!(),
 			};
 
@@ -72,21 +185,113 @@ impl ast::Node for Function {
 		for i in &self.nodes {
 			i.emit(ctx);
 		}
+
+		// falling off the end returns 0, instead of running into the code after it
+		if !matches!(self.nodes.last(), Some(ast::BoxedNode::ReturnStatement(_))) {
+			// xor eax, eax
+			ctx.emit(&[0x31, 0xC0]);
+
+			// mov rsp, rbp
+			ctx.emit(&[0x48, 0x89, 0xEC]);
+
+			// pop rbp
+			ctx.emit(&[0x5D]);
+
+			// ret
+			ctx.emit(&[0xC3]);
+		}
 	}
 
 	fn pre_type_check(&self, ctx: &mut tc::Context) {
-		ctx.define_function(
+		ctx.declare_function(
 			self.name.clone(),
+			&self.location,
 			self.return_type.clone(),
 			self.params.clone(),
 		);
+
+		if let (Some(module_name), false) = (&self.module_name, self.is_public) {
+			ctx.define_private_function(self.name.clone(), module_name.clone());
+		}
+
+		if self.is_generic() {
+			ctx.define_generic_function(self.name.clone(), self.clone());
+		}
 	}
 
-	fn type_check(&self, ctx: &mut tc::Context) -> Result<tc::Type> {
-		for i in &self.nodes {
-			i.type_check(ctx)?;
+	fn type_check(&mut self, ctx: &mut tc::Context) -> Result<tc::Type> {
+		// generic functions are checked once per instantiation instead
+		if self.is_generic() {
+			return Ok(self.return_type.clone());
+		}
+
+		if self.params.len() > ast::ARGUMENT_REGISTERS {
+			return Err(Diagnostic::error(
+				Code::TooManyArguments,
+				format!(
+					"'{}' takes more than {} parameters",
+					self.name,
+					ast::ARGUMENT_REGISTERS
+				),
+			)
+			.into());
+		}
+
+		ctx.set_module(self.module_name.clone());
+		ctx.set_type_arguments(self.type_arguments.clone());
+		ctx.set_return_type(self.return_type.clone());
+		ctx.start_inference(self.variables.len());
+
+		let error_count = ctx.error_count();
+
+		for i in &mut self.nodes {
+			if let Err(x) = i.type_check(ctx) {
+				ctx.report(x);
+			}
+		}
+
+		// the types can't be inferred properly after an error, it'd only add more noise
+		if ctx.error_count() != error_count {
+			return Ok(self.return_type.clone());
+		}
+
+		for (i, (name, typ)) in self.variables.iter_mut().enumerate() {
+			*typ = ctx.resolve(&tc::Type::Variable(i));
+
+			if !typ.is_resolved() {
+				return Err(Diagnostic::error(
+					Code::CannotInfer,
+					format!("cannot infer the type of '{}' in '{}'", name, self.name),
+				)
+				.into());
+			}
+		}
+
+		if !self.return_type.is_resolved() {
+			self.return_type = ctx.resolve(&self.return_type);
+			ctx.define_function(
+				self.name.clone(),
+				self.return_type.clone(),
+				self.params.clone(),
+			);
 		}
 
 		return Ok(self.return_type.clone());
 	}
+
+	fn lint(&self, ctx: &mut lint::Context) {
+		let first_local = self.params.iter().map(|x| x.get_size() as u32).sum();
+		let is_exported = self.module_name.is_some() && self.is_public;
+
+		ctx.start_function(
+			&self.name,
+			&self.location,
+			&self.allowed_lints,
+			is_exported,
+			first_local,
+		);
+
+		let pending = ctx.lint_statements(&self.nodes);
+		ctx.end_function(pending);
+	}
 }
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+
+use crate::asdf::{ast, byte_code, lint, location::Location, tc, Code, Diagnostic, Result};
+
+#[derive(Debug, Clone)]
+pub struct Implementation {
+	trait_name: Option<String>,
+	self_type: tc::Type,
+	functions: Vec<(String, ast::Function)>,
+	location: Location,
+}
+
+impl Implementation {
+	pub fn new(
+		trait_name: Option<String>,
+		self_type: tc::Type,
+		functions: Vec<ast::Function>,
+		location: Location,
+	) -> Self {
+		let functions = functions
+			.into_iter()
+			.map(|mut x| {
+				let method_name = x.get_name().to_string();
+
+				x.set_name(match &trait_name {
+					Some(trait_name) => {
+						format!("<{} as {}>::{}", self_type, trait_name, method_name)
+					}
+					None => format!("{}::{}", self_type, method_name),
+				});
+
+				(method_name, x)
+			})
+			.collect();
+
+		return Self {
+			trait_name,
+			self_type,
+			functions,
+			location,
+		};
+	}
+
+	pub fn get_functions(&self) -> impl Iterator<Item = &ast::Function> {
+		return self.functions.iter().map(|(_, x)| x);
+	}
+}
+
+impl ast::Node for Implementation {
+	fn get_location(&self) -> &Location {
+		return &self.location;
+	}
+
+	fn emit(&self, ctx: &mut byte_code::Context) {
+		for (_, function) in &self.functions {
+			function.emit(ctx);
+		}
+	}
+
+	fn pre_type_check(&self, ctx: &mut tc::Context) {
+		for (method_name, function) in &self.functions {
+			function.pre_type_check(ctx);
+
+			ctx.define_method(
+				self.self_type.clone(),
+				method_name.clone(),
+				self.trait_name.is_none(),
+				function.get_name().to_string(),
+			);
+		}
+	}
+
+	fn type_check(&mut self, ctx: &mut tc::Context) -> Result<tc::Type> {
+		let Some(trait_name) = self.trait_name.clone() else {
+			// a method that doesn't check is reported, and the rest are still checked
+			for (_, function) in &mut self.functions {
+				if let Err(mut x) = function.type_check(ctx) {
+					x.set_location(ast::Node::get_location(function));
+					ctx.report(x);
+				}
+			}
+
+			return Ok(tc::Type::None);
+		};
+
+		let Some(methods) = ctx.find_trait(&trait_name) else {
+			return Err(Diagnostic::error(
+				Code::UnknownTrait,
+				format!("cannot find trait '{}'", trait_name),
+			)
+			.into());
+		};
+
+		let type_arguments = HashMap::from([("Self".to_string(), self.self_type.clone())]);
+
+		for (method_name, typ) in &methods {
+			let Some((_, function)) = self.functions.iter().find(|(x, _)| x == method_name) else {
+				ctx.report(
+					Diagnostic::error(
+						Code::MissingTraitMethod,
+						format!(
+							"missing method '{}' in implementation of '{}' for '{}'",
+							method_name, trait_name, self.self_type
+						),
+					)
+					.with_primary(self.location.clone())
+					.into(),
+				);
+
+				continue;
+			};
+
+			let expected = typ.substitute(&type_arguments);
+
+			if function.get_type() != expected {
+				ctx.report(
+					Diagnostic::error(
+						Code::TraitMethodType,
+						format!(
+							"method '{}' of '{}' for '{}' should have the type '{}', got '{}'",
+							method_name,
+							trait_name,
+							self.self_type,
+							expected,
+							function.get_type()
+						),
+					)
+					.with_primary(self.location.clone())
+					.into(),
+				);
+			}
+		}
+
+		for (method_name, function) in &mut self.functions {
+			if !methods.iter().any(|(x, _)| x == method_name) {
+				ctx.report(
+					Diagnostic::error(
+						Code::NotATraitMethod,
+						format!(
+							"method '{}' is not a member of trait '{}'",
+							method_name, trait_name
+						),
+					)
+					.with_primary(self.location.clone())
+					.into(),
+				);
+
+				continue;
+			}
+
+			if let Err(mut x) = function.type_check(ctx) {
+				x.set_location(ast::Node::get_location(function));
+				ctx.report(x);
+			}
+		}
+
+		return Ok(tc::Type::None);
+	}
+
+	fn lint(&self, ctx: &mut lint::Context) {
+		for (_, function) in &self.functions {
+			function.lint(ctx);
+		}
+	}
+}
@@ -0,0 +1,35 @@
+use crate::asdf::{ast, byte_code, location::Location, tc, Result};
+
+// the module itself is loaded by the instance, before anything is type checked
+#[derive(Debug, Clone)]
+pub struct Import {
+	module_name: String,
+	location: Location,
+}
+
+impl Import {
+	pub fn new(module_name: String, location: Location) -> Self {
+		return Self {
+			module_name,
+			location,
+		};
+	}
+
+	pub fn get_module_name(&self) -> &str {
+		return &self.module_name;
+	}
+}
+
+impl ast::Node for Import {
+	fn get_location(&self) -> &Location {
+		return &self.location;
+	}
+
+	fn emit(&self, _: &mut byte_code::Context) {}
+
+	fn pre_type_check(&self, _: &mut tc::Context) {}
+
+	fn type_check(&mut self, _: &mut tc::Context) -> Result<tc::Type> {
+		return Ok(tc::Type::None);
+	}
+}
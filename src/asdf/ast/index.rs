@@ -0,0 +1,97 @@
+use crate::asdf::{
+	ast, byte_code, lint, location::Location, runtime, tc, Code, Diagnostic, Result,
+};
+
+#[derive(Debug, Clone)]
+pub struct Index {
+	value: Box<ast::BoxedNode>,
+	index: Box<ast::BoxedNode>,
+	// `None` when indexing into a str
+	element_type: Option<tc::Type>,
+	location: Location,
+}
+
+impl Index {
+	pub fn new(value: ast::BoxedNode, index: ast::BoxedNode, location: Location) -> Self {
+		return Self {
+			value: Box::new(value),
+			index: Box::new(index),
+			element_type: None,
+			location,
+		};
+	}
+}
+
+impl ast::Node for Index {
+	fn get_location(&self) -> &Location {
+		return &self.location;
+	}
+
+	fn emit(&self, ctx: &mut byte_code::Context) {
+		let Some(element_type) = &self.element_type else {
+			ast::CallStatement::emit_runtime_call(
+				ctx,
+				runtime::str_index as u64,
+				&[&self.value, &self.index],
+			);
+
+			return;
+		};
+
+		self.value.emit(ctx);
+
+		// push rax
+		ctx.emit(&[0x50]);
+		ctx.push(0x8);
+
+		self.index.emit(ctx);
+
+		// mov rcx, rax
+		ctx.emit(&[0x48, 0x89, 0xC1]);
+
+		// pop rax
+		ctx.emit(&[0x58]);
+		ctx.pop(0x8);
+
+		match element_type {
+			// pointers to chars point to actual bytes, like the ones coming from c
+			// movzx rax, byte [rax + rcx]
+			tc::Type::Char => ctx.emit(&[0x48, 0x0F, 0xB6, 0x04, 0x08]),
+			// mov rax, [rax + rcx * 8]
+			_ => ctx.emit(&[0x48, 0x8B, 0x04, 0xC8]),
+		};
+	}
+
+	fn pre_type_check(&self, ctx: &mut tc::Context) {
+		self.value.pre_type_check(ctx);
+		self.index.pre_type_check(ctx);
+	}
+
+	fn type_check(&mut self, ctx: &mut tc::Context) -> Result<tc::Type> {
+		let value = self.value.type_check(ctx)?;
+
+		let index = self.index.type_check(ctx)?;
+		ctx.unify(&tc::Type::I64, &index)?;
+
+		if let tc::Type::Pointer(element_type) = ctx.resolve(&value) {
+			if !element_type.is_resolved() {
+				return Err(Diagnostic::error(
+					Code::CannotInfer,
+					format!("cannot infer the type of the elements of '{}'", value),
+				)
+				.into());
+			}
+
+			self.element_type = Some(*element_type.clone());
+			return Ok(*element_type);
+		}
+
+		ctx.unify(&tc::Type::Str, &value)?;
+		return Ok(tc::Type::Char);
+	}
+
+	fn lint(&self, ctx: &mut lint::Context) {
+		self.value.lint(ctx);
+		self.index.lint(ctx);
+	}
+}
@@ -0,0 +1,69 @@
+use crate::asdf::{ast, byte_code, lint, location::Location, tc, Code, Diagnostic, Result};
+
+#[derive(Debug, Clone)]
+pub struct Indirection {
+	node: Box<ast::BoxedNode>,
+	// pointers to chars point to actual bytes, so they're loaded and stored one byte at a time
+	points_to_char: bool,
+	location: Location,
+}
+
+impl Indirection {
+	pub fn new(node: ast::BoxedNode, location: Location) -> Self {
+		return Self {
+			node: Box::new(node),
+			points_to_char: false,
+			location,
+		};
+	}
+
+	pub fn points_to_char(&self) -> bool {
+		return self.points_to_char;
+	}
+}
+
+impl ast::Node for Indirection {
+	fn get_location(&self) -> &Location {
+		return &self.location;
+	}
+
+	fn emit(&self, ctx: &mut byte_code::Context) {
+		// the pointer already is the address
+		self.node.emit(ctx);
+	}
+
+	fn pre_type_check(&self, ctx: &mut tc::Context) {
+		self.node.pre_type_check(ctx);
+	}
+
+	fn type_check(&mut self, ctx: &mut tc::Context) -> Result<tc::Type> {
+		let pointer = self.node.type_check(ctx)?;
+		let typ = ctx.new_variable();
+
+		ctx.unify(&tc::Type::Pointer(Box::new(typ.clone())), &pointer)?;
+		let typ = ctx.resolve(&typ);
+
+		// the size has to be known to load or store through it
+		if !typ.is_resolved() {
+			return Err(Diagnostic::error(
+				Code::CannotInfer,
+				format!(
+					"cannot infer the type '{}' points to",
+					ctx.resolve(&pointer)
+				),
+			)
+			.into());
+		}
+
+		self.points_to_char = typ == tc::Type::Char;
+		return Ok(tc::Type::Reference(Box::new(typ)));
+	}
+
+	fn is_reference(&self) -> bool {
+		return true;
+	}
+
+	fn lint(&self, ctx: &mut lint::Context) {
+		self.node.lint(ctx);
+	}
+}
@@ -1,17 +1,22 @@
-use crate::asdf::{ast, byte_code, tc, Result};
+use crate::asdf::{ast, byte_code, location::Location, tc, Result};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Integer {
 	value: i64,
+	location: Location,
 }
 
 impl Integer {
-	pub fn new(value: i64) -> Self {
-		return Self { value };
+	pub fn new(value: i64, location: Location) -> Self {
+		return Self { value, location };
 	}
 }
 
 impl ast::Node for Integer {
+	fn get_location(&self) -> &Location {
+		return &self.location;
+	}
+
 	fn emit(&self, ctx: &mut byte_code::Context) {
 		// mov rax, self.value
 		ctx.emit(
@@ -21,7 +26,7 @@ impl ast::Node for Integer {
 		);
 	}
 
-	fn type_check(&self, _: &mut tc::Context) -> Result<tc::Type> {
+	fn type_check(&mut self, _: &mut tc::Context) -> Result<tc::Type> {
 		return Ok(tc::Type::I64);
 	}
 }
@@ -0,0 +1,101 @@
+use crate::asdf::{ast, byte_code, lint, location::Location, tc, Code, Diagnostic, Result};
+
+#[derive(Debug, Clone)]
+pub struct MethodCall {
+	method_name: String,
+	call: ast::CallStatement,
+	location: Location,
+}
+
+impl MethodCall {
+	pub fn new(
+		receiver: ast::BoxedNode,
+		method_name: String,
+		arguments: Vec<ast::BoxedNode>,
+		location: Location,
+	) -> Self {
+		// the receiver is passed as the first argument, the callee is filled in by the type checker
+		let call = ast::CallStatement::new(
+			ast::Relative::new(method_name.clone(), location.clone()).into(),
+			[vec![receiver], arguments].concat(),
+			location.clone(),
+		);
+
+		return Self {
+			method_name,
+			call,
+			location,
+		};
+	}
+}
+
+impl ast::Node for MethodCall {
+	fn get_location(&self) -> &Location {
+		return &self.location;
+	}
+
+	fn emit(&self, ctx: &mut byte_code::Context) {
+		self.call.emit(ctx);
+	}
+
+	fn pre_type_check(&self, ctx: &mut tc::Context) {
+		self.call.pre_type_check(ctx);
+	}
+
+	fn type_check(&mut self, ctx: &mut tc::Context) -> Result<tc::Type> {
+		let receiver = self.call.get_arguments_mut()[0].type_check(ctx)?;
+		let receiver = ctx.resolve(&receiver);
+
+		if !receiver.is_resolved() {
+			return Err(Diagnostic::error(
+				Code::CannotInfer,
+				format!(
+					"cannot infer the type of the receiver of '{}'",
+					self.method_name
+				),
+			)
+			.into());
+		}
+
+		let Some(function_name) = ctx.find_method(&receiver, &self.method_name)? else {
+			return Err(Diagnostic::error(
+				Code::UnknownMethod,
+				format!(
+					"no method named '{}' found for '{}'",
+					self.method_name, receiver
+				),
+			)
+			.into());
+		};
+
+		// a receiver living on the stack gets borrowed for methods taking `*Self`
+		let mut typ = receiver.clone();
+
+		if let Some((_, params)) = ctx.find_function(&function_name) {
+			let arguments = self.call.get_arguments_mut();
+
+			if let (Some(tc::Type::Pointer(x)), ast::BoxedNode::Dereference(dereference)) =
+				(params.first(), &arguments[0])
+			{
+				if **x == receiver {
+					arguments[0] = ast::AddressOf::new(
+						dereference.get_node().clone(),
+						ast::Node::get_location(dereference).clone(),
+					)
+					.into();
+
+					typ = tc::Type::Pointer(Box::new(receiver));
+				}
+			}
+		}
+
+		self.call
+			.set_function(ast::Relative::new(function_name, self.location.clone()).into());
+
+		return self.call.type_check_with_receiver(ctx, Some(typ));
+	}
+
+	fn lint(&self, ctx: &mut lint::Context) {
+		self.call.lint(ctx);
+	}
+}
@@ -1,39 +1,51 @@
+mod address_of;
 mod binary_operation;
 mod call_statement;
 mod dereference;
-mod extern_function;
 mod extern_string;
 mod function;
 mod if_statement;
+mod implementation;
+mod import;
+mod index;
+mod indirection;
 mod integer;
+mod method_call;
 mod node;
 mod relative;
 mod return_statement;
 mod scope;
 mod stack;
+mod trait_definition;
 mod while_statement;
 
+pub use address_of::*;
 pub use binary_operation::*;
 pub use call_statement::*;
 pub use dereference::*;
-pub use extern_function::*;
 pub use extern_string::*;
 pub use function::*;
 pub use if_statement::*;
+pub use implementation::*;
+pub use import::*;
+pub use index::*;
+pub use indirection::*;
 pub use integer::*;
+pub use method_call::*;
 pub use node::*;
 pub use relative::*;
 pub use return_statement::*;
 pub use scope::*;
 pub use stack::*;
+pub use trait_definition::*;
 pub use while_statement::*;
 
-use crate::asdf::{byte_code, tc, Result};
+use crate::asdf::{byte_code, lint, location::Location, tc, Result};
 
 macro_rules! helper {
 	($name:ident, $($types:ident),*,) => {
 
-		#[derive(Debug)]
+		#[derive(Debug, Clone)]
 		pub enum $name {
 			$(
 				$types($types),
@@ -58,10 +70,30 @@ macro_rules! helper {
 				}
 			}
 
-			pub fn type_check(&self, ctx: &mut tc::Context) -> Result<tc::Type> {
+			// errors that don't point anywhere yet point to the innermost node they came from
+			pub fn type_check(&mut self, ctx: &mut tc::Context) -> Result<tc::Type> {
 				return match self {
 					$(
-						Self::$types(x) => x.type_check(ctx),
+						Self::$types(x) => x.type_check(ctx).map_err(|mut error| {
+							error.set_location(x.get_location());
+							error
+						}),
+					)*
+				};
+			}
+
+			pub fn lint(&self, ctx: &mut lint::Context) {
+				match self {
+					$(
+						Self::$types(x) => x.lint(ctx),
+					)*
+				}
+			}
+
+			pub fn get_location(&self) -> &Location {
+				return match self {
+					$(
+						Self::$types(x) => x.get_location(),
 					)*
 				};
 			}
@@ -87,17 +119,23 @@ macro_rules! helper {
 
 helper!(
 	BoxedNode,
+	AddressOf,
 	BinaryOperation,
 	CallStatement,
 	Dereference,
-	ExternFunction,
 	ExternString,
 	Function,
 	IfStatement,
+	Implementation,
+	Import,
+	Index,
+	Indirection,
 	Integer,
+	MethodCall,
 	Relative,
 	ReturnStatement,
 	Scope,
 	Stack,
+	TraitDefinition,
 	WhileStatement,
 );
@@ -1,10 +1,14 @@
-use crate::asdf::{byte_code, tc, Result};
+use crate::asdf::{byte_code, lint, location::Location, tc, Result};
 
 pub trait Node: std::fmt::Debug {
+	fn get_location(&self) -> &Location;
+
 	fn emit(&self, ctx: &mut byte_code::Context);
 
 	fn pre_type_check(&self, _: &mut tc::Context) {}
-	fn type_check(&self, ctx: &mut tc::Context) -> Result<tc::Type>;
+	fn type_check(&mut self, ctx: &mut tc::Context) -> Result<tc::Type>;
+
+	fn lint(&self, _: &mut lint::Context) {}
 
 	fn is_reference(&self) -> bool {
 		return false;
@@ -1,28 +1,79 @@
-use crate::asdf::{ast, byte_code, tc, Result};
+use crate::asdf::{ast, byte_code, lint, location::Location, tc, Code, Diagnostic, Result};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Relative {
 	function_name: String,
+	instance: Option<String>,
+	address: Option<u64>,
+	location: Location,
 }
 
 impl Relative {
-	pub fn new(function_name: String) -> Self {
-		return Self { function_name };
+	pub fn new(function_name: String, location: Location) -> Self {
+		return Self {
+			function_name,
+			instance: None,
+			address: None,
+			location,
+		};
+	}
+
+	pub fn get_function_name(&self) -> &str {
+		return &self.function_name;
+	}
+
+	pub fn set_instance(&mut self, instance: String) {
+		self.instance = Some(instance);
 	}
 }
 
 impl ast::Node for Relative {
+	fn get_location(&self) -> &Location {
+		return &self.location;
+	}
+
 	fn emit(&self, ctx: &mut byte_code::Context) {
+		if let Some(address) = self.address {
+			// mov rax, address
+			ctx.emit(
+				[[0x48, 0xB8].to_vec(), address.to_ne_bytes().into()]
+					.concat()
+					.as_slice(),
+			);
+
+			return;
+		}
+
+		let target = self.instance.as_ref().unwrap_or(&self.function_name);
+
 		// lea rax, [rip + target]
 		let ins = ctx.emit(&[0x48, 0x8D, 0x05]);
-		ctx.late_initialize_relative(ins, self.function_name.clone());
+		ctx.late_initialize_relative(ins, target.clone());
 	}
 
-	fn type_check(&self, ctx: &mut tc::Context) -> Result<tc::Type> {
-		let Some((return_type, argument_types)) = ctx.find_function(&self.function_name) else {
-			return Err(format!("cannot find function '{}'", self.function_name));
+	fn type_check(&mut self, ctx: &mut tc::Context) -> Result<tc::Type> {
+		self.function_name = ctx.qualify(&self.function_name)?;
+
+		// functions defined in the script shadow the extern ones
+		if let Some((return_type, argument_types)) = ctx.find_function(&self.function_name) {
+			return Ok(tc::Type::Function(Box::new(return_type), argument_types));
+		}
+
+		let Some((address, return_type, argument_types)) =
+			ctx.find_extern_function(&self.function_name)
+		else {
+			return Err(Diagnostic::error(
+				Code::UnknownFunction,
+				format!("cannot find function '{}'", self.function_name),
+			)
+			.into());
 		};
 
+		self.address = Some(address);
 		return Ok(tc::Type::Function(Box::new(return_type), argument_types));
 	}
+
+	fn lint(&self, ctx: &mut lint::Context) {
+		ctx.reference(&self.function_name);
+	}
 }
@@ -1,19 +1,25 @@
-use crate::asdf::{ast, byte_code, tc, Result};
+use crate::asdf::{ast, byte_code, lint, location::Location, tc, Result};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ReturnStatement {
 	value: Box<ast::BoxedNode>,
+	location: Location,
 }
 
 impl ReturnStatement {
-	pub fn new(value: ast::BoxedNode) -> Self {
+	pub fn new(value: ast::BoxedNode, location: Location) -> Self {
 		return Self {
 			value: Box::new(value),
+			location,
 		};
 	}
 }
 
 impl ast::Node for ReturnStatement {
+	fn get_location(&self) -> &Location {
+		return &self.location;
+	}
+
 	fn emit(&self, ctx: &mut byte_code::Context) {
 		self.value.emit(ctx);
 
@@ -31,11 +37,14 @@ impl ast::Node for ReturnStatement {
 		self.value.pre_type_check(ctx);
 	}
 
-	fn type_check(&self, ctx: &mut tc::Context) -> Result<tc::Type> {
-		let tc::Type::I64 = self.value.type_check(ctx)? else {
-			return Err(format!(""));
-		};
+	fn type_check(&mut self, ctx: &mut tc::Context) -> Result<tc::Type> {
+		let value_type = self.value.type_check(ctx)?;
+		ctx.unify(&ctx.get_return_type().clone(), &value_type)?;
 
 		return Ok(tc::Type::None);
 	}
+
+	fn lint(&self, ctx: &mut lint::Context) {
+		self.value.lint(ctx);
+	}
 }
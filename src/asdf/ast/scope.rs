@@ -1,17 +1,26 @@
-use crate::asdf::{ast, byte_code, tc, Result};
+use crate::asdf::{ast, byte_code, lint, location::Location, tc, Result};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Scope {
 	nodes: Vec<ast::BoxedNode>,
+	location: Location,
 }
 
 impl Scope {
-	pub fn new(nodes: Vec<ast::BoxedNode>) -> Self {
-		return Self { nodes };
+	pub fn new(nodes: Vec<ast::BoxedNode>, location: Location) -> Self {
+		return Self { nodes, location };
+	}
+
+	pub fn get_nodes(&self) -> &[ast::BoxedNode] {
+		return &self.nodes;
 	}
 }
 
 impl ast::Node for Scope {
+	fn get_location(&self) -> &Location {
+		return &self.location;
+	}
+
 	fn emit(&self, ctx: &mut byte_code::Context) {
 		for i in &self.nodes {
 			i.emit(ctx);
@@ -24,11 +33,18 @@ impl ast::Node for Scope {
 		}
 	}
 
-	fn type_check(&self, ctx: &mut tc::Context) -> Result<tc::Type> {
-		for i in &self.nodes {
-			i.type_check(ctx)?;
+	fn type_check(&mut self, ctx: &mut tc::Context) -> Result<tc::Type> {
+		// a statement that doesn't check is reported, and the rest are still checked
+		for i in &mut self.nodes {
+			if let Err(x) = i.type_check(ctx) {
+				ctx.report(x);
+			}
 		}
 
 		return Ok(tc::Type::None);
 	}
+
+	fn lint(&self, ctx: &mut lint::Context) {
+		ctx.lint_statements(&self.nodes);
+	}
 }
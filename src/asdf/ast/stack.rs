@@ -1,25 +1,47 @@
 use std::num::Wrapping;
 
-use crate::asdf::{ast, byte_code, tc, Result};
+use crate::asdf::{ast, byte_code, lint, location::Location, tc, Result};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Stack {
+	name: String,
 	value_type: tc::Type,
 	offset: u32,
 	size: u32,
+	location: Location,
 }
 
 impl Stack {
-	pub fn new(value_type: tc::Type, offset: u32, size: u32) -> Self {
+	pub fn new(
+		name: String,
+		value_type: tc::Type,
+		offset: u32,
+		size: u32,
+		location: Location,
+	) -> Self {
 		return Self {
+			name,
 			value_type,
 			offset,
 			size,
+			location,
 		};
 	}
+
+	pub fn get_name(&self) -> &str {
+		return &self.name;
+	}
+
+	pub fn get_offset(&self) -> u32 {
+		return self.offset;
+	}
 }
 
 impl ast::Node for Stack {
+	fn get_location(&self) -> &Location {
+		return &self.location;
+	}
+
 	fn emit(&self, ctx: &mut byte_code::Context) {
 		let offset = (Wrapping(0) - Wrapping(self.offset + self.size)).0;
 
@@ -29,11 +51,18 @@ impl ast::Node for Stack {
 		ctx.emit(bytes.as_slice());
 	}
 
-	fn type_check(&self, _: &mut tc::Context) -> Result<tc::Type> {
-		return Ok(tc::Type::Reference(Box::new(self.value_type.clone())));
+	fn type_check(&mut self, ctx: &mut tc::Context) -> Result<tc::Type> {
+		return Ok(tc::Type::Reference(Box::new(
+			ctx.substitute(&self.value_type),
+		)));
 	}
 
 	fn is_reference(&self) -> bool {
 		return true;
 	}
+
+	// an assignment is the only place a local isn't read, and that's handled by the assignment
+	fn lint(&self, ctx: &mut lint::Context) {
+		ctx.read(self.offset);
+	}
 }
@@ -0,0 +1,34 @@
+use crate::asdf::{ast, byte_code, location::Location, tc, Result};
+
+#[derive(Debug, Clone)]
+pub struct TraitDefinition {
+	name: String,
+	methods: Vec<(String, tc::Type)>,
+	location: Location,
+}
+
+impl TraitDefinition {
+	pub fn new(name: String, methods: Vec<(String, tc::Type)>, location: Location) -> Self {
+		return Self {
+			name,
+			methods,
+			location,
+		};
+	}
+}
+
+impl ast::Node for TraitDefinition {
+	fn get_location(&self) -> &Location {
+		return &self.location;
+	}
+
+	fn emit(&self, _: &mut byte_code::Context) {}
+
+	fn pre_type_check(&self, ctx: &mut tc::Context) {
+		ctx.define_trait(self.name.clone(), self.methods.clone());
+	}
+
+	fn type_check(&mut self, _: &mut tc::Context) -> Result<tc::Type> {
+		return Ok(tc::Type::None);
+	}
+}
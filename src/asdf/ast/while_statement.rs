@@ -1,21 +1,27 @@
-use crate::asdf::{ast, byte_code, tc, Result};
+use crate::asdf::{ast, byte_code, lint, location::Location, tc, Result};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct WhileStatement {
 	condition: Box<ast::BoxedNode>,
 	scope: ast::Scope,
+	location: Location,
 }
 
 impl WhileStatement {
-	pub fn new(condition: ast::BoxedNode, scope: ast::Scope) -> Self {
+	pub fn new(condition: ast::BoxedNode, scope: ast::Scope, location: Location) -> Self {
 		return Self {
 			condition: Box::new(condition),
 			scope,
+			location,
 		};
 	}
 }
 
 impl ast::Node for WhileStatement {
+	fn get_location(&self) -> &Location {
+		return &self.location;
+	}
+
 	fn emit(&self, ctx: &mut byte_code::Context) {
 		let condition = ctx.new_label();
 		self.condition.emit(ctx);
@@ -44,12 +50,16 @@ impl ast::Node for WhileStatement {
 		self.scope.pre_type_check(ctx);
 	}
 
-	fn type_check(&self, ctx: &mut tc::Context) -> Result<tc::Type> {
-		let tc::Type::I64 = self.condition.type_check(ctx)? else {
-			return Err(format!(""));
-		};
+	fn type_check(&mut self, ctx: &mut tc::Context) -> Result<tc::Type> {
+		let condition = self.condition.type_check(ctx)?;
+		ctx.unify(&tc::Type::I64, &condition)?;
 
 		self.scope.type_check(ctx)?;
 		return Ok(tc::Type::None);
 	}
+
+	fn lint(&self, ctx: &mut lint::Context) {
+		self.condition.lint(ctx);
+		self.scope.lint(ctx);
+	}
 }
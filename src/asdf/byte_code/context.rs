@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use crate::asdf::byte_code;
+use crate::asdf::{byte_code, runtime, Code, Diagnostic, Result};
 
 #[derive(Debug)]
 pub struct Context {
@@ -8,6 +8,7 @@ pub struct Context {
 	stack_size: usize,
 	late_init: Vec<(String, usize)>,
 	functions: HashMap<String, usize>,
+	literals: Vec<runtime::OwnedStr>,
 }
 
 impl Context {
@@ -17,6 +18,7 @@ impl Context {
 			stack_size: usize::default(),
 			late_init: Vec::new(),
 			functions: HashMap::new(),
+			literals: Vec::new(),
 		};
 	}
 
@@ -42,7 +44,68 @@ impl Context {
 		return self.instructions.len();
 	}
 
-	pub fn instructions(&mut self) -> Vec<byte_code::Instruction> {
+	// appends a separately emitted context, references to functions in either one are resolved
+	// once all of them are linked together
+	pub fn link(&mut self, other: Self) -> Result<()> {
+		let offset = self.instructions.len();
+
+		for (name, label) in other.functions {
+			if self
+				.functions
+				.insert(name.clone(), label + offset)
+				.is_some()
+			{
+				return Err(Diagnostic::error(
+					Code::DuplicateDefinition,
+					format!("'{}' is defined more than once", name),
+				)
+				.into());
+			}
+		}
+
+		for mut i in other.instructions {
+			i.offset_target(offset);
+			self.instructions.push(i);
+		}
+
+		self.late_init.extend(
+			other
+				.late_init
+				.into_iter()
+				.map(|(name, ins)| (name, ins + offset)),
+		);
+
+		self.literals.extend(other.literals);
+		return Ok(());
+	}
+
+	// for code that's added on top of an already compiled module, references to the functions
+	// in there are loaded from their address instead
+	pub fn link_addresses(&mut self, find: impl Fn(&str) -> Option<u64>) -> Result<()> {
+		if let Some(name) = self.functions.keys().find(|x| find(x).is_some()) {
+			return Err(Diagnostic::error(
+				Code::DuplicateDefinition,
+				format!("'{}' is defined more than once", name),
+			)
+			.into());
+		}
+
+		for (name, ins) in std::mem::take(&mut self.late_init) {
+			let Some(address) = find(&name).filter(|_| !self.functions.contains_key(&name)) else {
+				self.late_init.push((name, ins));
+				continue;
+			};
+
+			// mov rax, address
+			*self.get_mut(ins).unwrap() = byte_code::Instruction::new(
+				&[[0x48, 0xB8].to_vec(), address.to_ne_bytes().into()].concat(),
+			);
+		}
+
+		return Ok(());
+	}
+
+	pub fn instructions(&mut self) -> Result<Vec<byte_code::Instruction>> {
 		let mut position = usize::default();
 
 		for i in &mut self.instructions {
@@ -50,7 +113,14 @@ impl Context {
 		}
 
 		for (name, ins) in std::mem::take(&mut self.late_init) {
-			let fun = self.functions.get(&name).unwrap().clone();
+			let Some(fun) = self.functions.get(&name).cloned() else {
+				return Err(Diagnostic::error(
+					Code::UndefinedReference,
+					format!("undefined reference to '{}'", name),
+				)
+				.into());
+			};
+
 			self.get_mut(ins).unwrap().set_target(fun, 0x4);
 		}
 
@@ -62,7 +132,7 @@ impl Context {
 		}
 
 		self.instructions = instructions;
-		return self.instructions.clone();
+		return Ok(self.instructions.clone());
 	}
 
 	// a temporary solution to keep track of the stack alignment
@@ -96,4 +166,25 @@ impl Context {
 
 		return self.get(*ins)?.get_position();
 	}
+
+	pub fn get_function_offsets(&self) -> HashMap<String, usize> {
+		return self
+			.functions
+			.keys()
+			.filter_map(|x| Some((x.clone(), self.get_function_offset(x)?)))
+			.collect();
+	}
+
+	// returns the address of the literal, which is kept alive by the context
+	pub fn string_literal(&mut self, value: &str) -> u64 {
+		let literal = runtime::OwnedStr::new(value.as_bytes());
+		let ret = literal.get_address();
+
+		self.literals.push(literal);
+		return ret;
+	}
+
+	pub fn take_literals(&mut self) -> Vec<runtime::OwnedStr> {
+		return std::mem::take(&mut self.literals);
+	}
 }
@@ -22,6 +22,13 @@ impl Instruction {
 		self.target = Some((target, size));
 	}
 
+	// moves the target along with the instructions it points into
+	pub fn offset_target(&mut self, offset: usize) {
+		if let Some((target, _)) = &mut self.target {
+			*target += offset;
+		}
+	}
+
 	pub fn store_relative_position(&mut self, mut position: usize) -> usize {
 		self.position = Some(position);
 		position += self.get_size();
@@ -0,0 +1,180 @@
+use crate::asdf::Result;
+
+const PROT_READ: u64 = 0x1;
+const PROT_WRITE: u64 = 0x2;
+const PROT_EXEC: u64 = 0x4;
+
+const MAP_SHARED: u64 = 0x01;
+const MAP_PRIVATE: u64 = 0x02;
+const MAP_ANONYMOUS: u64 = 0x20;
+
+const MFD_CLOEXEC: u64 = 0x1;
+
+// memory for generated code, it's never writable and executable at the same time
+#[derive(Debug)]
+pub struct CodeMemory {
+	executable: *mut u8,
+	// a second, writable view of the same pages, only there when the code can be patched
+	writable: Option<*mut u8>,
+	size: usize,
+}
+
+impl CodeMemory {
+	// writes the code into read+write pages, which are then turned into read+execute ones
+	pub fn new(code: &[u8]) -> Result<Self> {
+		let size = code.len();
+
+		let ptr = mmap(
+			size,
+			PROT_READ | PROT_WRITE,
+			MAP_PRIVATE | MAP_ANONYMOUS,
+			u64::MAX,
+		)?;
+
+		let ret = Self {
+			executable: ptr,
+			writable: None,
+			size,
+		};
+
+		unsafe {
+			std::ptr::copy(code.as_ptr(), ptr, size);
+		}
+
+		syscall(
+			"mprotect",
+			10,
+			[ptr as u64, size as u64, PROT_READ | PROT_EXEC, 0, 0, 0],
+		)?;
+
+		return Ok(ret);
+	}
+
+	// maps the same memfd twice, once as read+write and once as read+execute, so the code can
+	// be patched later on without any page being both writable and executable
+	pub fn new_dual_mapped(code: &[u8]) -> Result<Self> {
+		let size = code.len();
+
+		let fd = syscall(
+			"memfd_create",
+			319,
+			[c"asdf".as_ptr() as u64, MFD_CLOEXEC, 0, 0, 0, 0],
+		)?;
+
+		let ret = Self::map_memfd(fd, size);
+
+		syscall("close", 3, [fd, 0, 0, 0, 0, 0])?;
+		let ret = ret?;
+
+		unsafe {
+			std::ptr::copy(code.as_ptr(), ret.writable.unwrap(), size);
+		}
+
+		return Ok(ret);
+	}
+
+	fn map_memfd(fd: u64, size: usize) -> Result<Self> {
+		syscall("ftruncate", 77, [fd, size as u64, 0, 0, 0, 0])?;
+
+		let writable = mmap(size, PROT_READ | PROT_WRITE, MAP_SHARED, fd)?;
+
+		let executable = match mmap(size, PROT_READ | PROT_EXEC, MAP_SHARED, fd) {
+			Ok(x) => x,
+			Err(x) => {
+				munmap(writable, size);
+				return Err(x);
+			}
+		};
+
+		return Ok(Self {
+			executable,
+			writable: Some(writable),
+			size,
+		});
+	}
+
+	/// # Safety
+	///
+	/// no thread may be running the patched bytes while they are written, and they have to leave
+	/// the code in a state that is valid to run
+	pub unsafe fn patch(&mut self, offset: usize, bytes: &[u8]) -> Result<()> {
+		let Some(writable) = self.writable else {
+			return Err(format!("code memory is not dual mapped, so it cannot be patched").into());
+		};
+
+		let end = offset.checked_add(bytes.len());
+
+		if end.is_none_or(|x| x > self.size) {
+			return Err(format!(
+				"cannot patch {} bytes at offset {}, the code is only {} bytes",
+				bytes.len(),
+				offset,
+				self.size
+			)
+			.into());
+		}
+
+		std::ptr::copy(bytes.as_ptr(), writable.add(offset), bytes.len());
+		return Ok(());
+	}
+
+	pub fn as_bytes(&self) -> &[u8] {
+		return unsafe { std::slice::from_raw_parts(self.executable, self.size) };
+	}
+
+	pub fn get_address(&self, offset: usize) -> *const u8 {
+		assert!(offset < self.size);
+		return unsafe { self.executable.add(offset) };
+	}
+}
+
+impl Drop for CodeMemory {
+	fn drop(&mut self) {
+		munmap(self.executable, self.size);
+
+		if let Some(writable) = self.writable {
+			munmap(writable, self.size);
+		}
+	}
+}
+
+fn mmap(size: usize, protection: u64, flags: u64, fd: u64) -> Result<*mut u8> {
+	let ret = syscall("mmap", 9, [0, size as u64, protection, flags, fd, 0])?;
+	return Ok(ret as *mut u8);
+}
+
+fn munmap(ptr: *mut u8, size: usize) {
+	// there is nothing sensible to do if this fails
+	let _ = syscall("munmap", 11, [ptr as u64, size as u64, 0, 0, 0, 0]);
+}
+
+fn syscall(name: &str, number: u64, arguments: [u64; 6]) -> Result<u64> {
+	let ret: i64;
+
+	unsafe {
+		std::arch::asm!(
+			"syscall",
+			inlateout("rax") number as i64 => ret,
+			in("rdi") arguments[0],
+			in("rsi") arguments[1],
+			in("rdx") arguments[2],
+			in("r10") arguments[3],
+			in("r8") arguments[4],
+			in("r9") arguments[5],
+			out("rcx") _,
+			out("r11") _,
+		);
+	}
+
+	// the kernel returns errors as -errno
+	if (-4095..0).contains(&ret) {
+		return Err(format!(
+			"{} failed: {}",
+			name,
+			std::io::Error::from_raw_os_error(-ret as i32)
+		)
+		.into());
+	}
+
+	return Ok(ret as u64);
+}
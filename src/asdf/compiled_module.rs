@@ -0,0 +1,44 @@
+use crate::asdf::{byte_code, CodeMemory, Result};
+
+// the generated code, mapped once and kept around until the module is dropped
+#[derive(Debug)]
+pub struct CompiledModule {
+	code: CodeMemory,
+	instruction_sizes: Vec<usize>,
+}
+
+impl CompiledModule {
+	pub fn new(instructions: &[byte_code::Instruction]) -> Result<Self> {
+		let mut code = vec![];
+		let mut instruction_sizes = vec![];
+
+		for i in instructions {
+			code.extend(i.get_instruction());
+			instruction_sizes.push(i.get_instruction().len());
+		}
+
+		return Ok(Self {
+			code: CodeMemory::new(&code)?,
+			instruction_sizes,
+		});
+	}
+
+	// the offset and bytes of every instruction
+	pub fn get_instructions(&self) -> Vec<(usize, &[u8])> {
+		let code = self.code.as_bytes();
+
+		let mut ret = vec![];
+		let mut offset = 0;
+
+		for size in &self.instruction_sizes {
+			ret.push((offset, &code[offset..offset + size]));
+			offset += size;
+		}
+
+		return ret;
+	}
+
+	pub fn get_address(&self, offset: usize) -> *const u8 {
+		return self.code.get_address(offset);
+	}
+}
@@ -0,0 +1,362 @@
+use std::fmt::{Display, Formatter};
+
+use crate::asdf::location::Location;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+	Error,
+	Warning,
+}
+
+// every code a diagnostic can have, each of them stands for one kind of problem only
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Code {
+	// the source doesn't lex or parse
+	Syntax,
+	UnknownFunction,
+	TypeMismatch,
+	CannotInfer,
+	// a call with a different number of arguments than the function has parameters
+	ArgumentCount,
+	// a module that's used without an `import`
+	NotImported,
+	UnknownMethod,
+	// an operator that isn't defined for the type of its operands
+	InvalidOperator,
+	InvalidMain,
+	DuplicateDefinition,
+	// raised by the running code, like indexing out of bounds
+	Runtime,
+	MissingMain,
+	UnknownTrait,
+	UnknownModule,
+	// a function that isn't `pub`, used from outside of its module
+	Private,
+	// more arguments than there are registers to pass them in
+	TooManyArguments,
+	AmbiguousMethod,
+	MissingTraitMethod,
+	TraitMethodType,
+	NotATraitMethod,
+	NotCallable,
+	NotDereferenceable,
+	NotAssignable,
+	NotAddressable,
+	UndefinedReference,
+	NothingCompiled,
+	InfiniteType,
+	// the formatter would have changed the meaning of the source
+	FormattingChanged,
+	UnusedVariable,
+	UnusedAssignment,
+	UnreachableCode,
+	DeadCode,
+}
+
+impl Code {
+	pub fn as_str(&self) -> &'static str {
+		return match self {
+			Self::Syntax => "E0001",
+			Self::UnknownFunction => "E0002",
+			Self::TypeMismatch => "E0003",
+			Self::CannotInfer => "E0004",
+			Self::ArgumentCount => "E0005",
+			Self::NotImported => "E0006",
+			Self::UnknownMethod => "E0007",
+			Self::InvalidOperator => "E0008",
+			Self::InvalidMain => "E0009",
+			Self::DuplicateDefinition => "E0010",
+			Self::Runtime => "E0011",
+			Self::MissingMain => "E0012",
+			Self::UnknownTrait => "E0013",
+			Self::UnknownModule => "E0014",
+			Self::Private => "E0015",
+			Self::TooManyArguments => "E0016",
+			Self::AmbiguousMethod => "E0017",
+			Self::MissingTraitMethod => "E0018",
+			Self::TraitMethodType => "E0019",
+			Self::NotATraitMethod => "E0020",
+			Self::NotCallable => "E0021",
+			Self::NotDereferenceable => "E0022",
+			Self::NotAssignable => "E0023",
+			Self::NotAddressable => "E0024",
+			Self::UndefinedReference => "E0025",
+			Self::NothingCompiled => "E0026",
+			Self::InfiniteType => "E0027",
+			Self::FormattingChanged => "E0028",
+			Self::UnusedVariable => "W0001",
+			Self::UnusedAssignment => "W0002",
+			Self::UnreachableCode => "W0003",
+			Self::DeadCode => "W0004",
+		};
+	}
+}
+
+// a single message, along with where it points to in the source
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+	severity: Severity,
+	code: Option<Code>,
+	message: String,
+	primary: Option<Location>,
+	secondary: Vec<(Location, String)>,
+	notes: Vec<String>,
+	// the line the primary location is on, once the source is known
+	source_line: Option<String>,
+}
+
+impl Diagnostic {
+	pub fn new<T: Into<String>>(severity: Severity, code: Code, message: T) -> Self {
+		return Self {
+			severity,
+			code: Some(code),
+			message: message.into(),
+			primary: None,
+			secondary: vec![],
+			notes: vec![],
+			source_line: None,
+		};
+	}
+
+	pub fn error<T: Into<String>>(code: Code, message: T) -> Self {
+		return Self::new(Severity::Error, code, message);
+	}
+
+	pub fn warning<T: Into<String>>(code: Code, message: T) -> Self {
+		return Self::new(Severity::Warning, code, message);
+	}
+
+	pub fn with_primary(mut self, location: Location) -> Self {
+		self.primary = Some(location);
+		return self;
+	}
+
+	pub fn with_secondary<T: Into<String>>(mut self, location: Location, label: T) -> Self {
+		self.secondary.push((location, label.into()));
+		return self;
+	}
+
+	pub fn with_note<T: Into<String>>(mut self, note: T) -> Self {
+		self.notes.push(note.into());
+		return self;
+	}
+
+	pub fn get_severity(&self) -> Severity {
+		return self.severity;
+	}
+
+	pub fn get_code(&self) -> Option<&'static str> {
+		return self.code.map(|x| x.as_str());
+	}
+
+	pub fn get_message(&self) -> &str {
+		return &self.message;
+	}
+
+	pub fn get_primary(&self) -> Option<&Location> {
+		return self.primary.as_ref();
+	}
+
+	// for the messages that don't know where they come from yet
+	pub fn set_location(&mut self, location: &Location) {
+		if self.primary.is_none() {
+			self.primary = Some(location.clone());
+		}
+	}
+
+	pub fn set_file_name(&mut self, file_name: &str) {
+		self.set_location(&Location::new(file_name, 0));
+	}
+
+	pub fn attach_source(&mut self, file_name: &str, source: &str) {
+		let Some(location) = &self.primary else {
+			return;
+		};
+
+		if location.get_file_name() != file_name || !location.has_span() {
+			return;
+		}
+
+		let offset = location.get_offset().min(source.len());
+
+		let start = source[..offset].rfind('\n').map(|x| x + 1).unwrap_or(0);
+		let end = source[offset..]
+			.find('\n')
+			.map(|x| x + offset)
+			.unwrap_or(source.len());
+
+		self.source_line = Some(source[start..end].trim_end_matches('\r').into());
+	}
+
+	// the line with the location underlined, the way rustc does it
+	fn fmt_source_line(
+		&self,
+		f: &mut Formatter<'_>,
+		location: &Location,
+		line: &str,
+	) -> std::fmt::Result {
+		let line_number = location.get_line_number().to_string();
+		let padding = " ".repeat(line_number.len());
+
+		// tabs would make the underline depend on the terminal
+		let expand = |x: &str| x.replace('\t', "    ");
+
+		let column = (location.get_column() as usize).saturating_sub(1);
+		let prefix: String = line.chars().take(column).collect();
+		let underlined: String = line
+			.chars()
+			.skip(column)
+			.take(location.get_length().max(1))
+			.collect();
+
+		writeln!(f, "{} |", padding)?;
+		writeln!(f, "{} | {}", line_number, expand(line))?;
+		write!(
+			f,
+			"{} | {}{}",
+			padding,
+			" ".repeat(expand(&prefix).chars().count()),
+			"^".repeat(expand(&underlined).chars().count().max(1))
+		)?;
+
+		return Ok(());
+	}
+}
+
+impl From<String> for Diagnostic {
+	fn from(message: String) -> Self {
+		return Self {
+			severity: Severity::Error,
+			code: None,
+			message,
+			primary: None,
+			secondary: vec![],
+			notes: vec![],
+			source_line: None,
+		};
+	}
+}
+
+impl Display for Diagnostic {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self.severity {
+			Severity::Error => write!(f, "error")?,
+			Severity::Warning => write!(f, "warning")?,
+		}
+
+		if let Some(code) = self.code {
+			write!(f, "[{}]", code.as_str())?;
+		}
+
+		write!(f, ": {}", self.message)?;
+
+		let mut padding = String::from(" ");
+
+		if let Some(location) = &self.primary {
+			padding = " ".repeat(location.get_line_number().to_string().len());
+			write!(f, "\n{}--> {}", padding, location)?;
+
+			if let Some(line) = &self.source_line {
+				writeln!(f)?;
+				self.fmt_source_line(f, location, line)?;
+			}
+		}
+
+		for (location, label) in &self.secondary {
+			write!(f, "\n{} = note: {} at {}", padding, label, location)?;
+		}
+
+		for note in &self.notes {
+			write!(f, "\n{} = note: {}", padding, note)?;
+		}
+
+		return Ok(());
+	}
+}
+
+// everything that was reported during one run
+#[derive(Debug, Clone, Default)]
+pub struct Diagnostics {
+	diagnostics: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+	pub fn new() -> Self {
+		return Self::default();
+	}
+
+	pub fn push(&mut self, diagnostic: Diagnostic) {
+		self.diagnostics.push(diagnostic);
+	}
+
+	pub fn extend(&mut self, diagnostics: Diagnostics) {
+		self.diagnostics.extend(diagnostics.diagnostics);
+	}
+
+	pub fn len(&self) -> usize {
+		return self.diagnostics.len();
+	}
+
+	pub fn is_empty(&self) -> bool {
+		return self.diagnostics.is_empty();
+	}
+
+	pub fn has_errors(&self) -> bool {
+		return self
+			.diagnostics
+			.iter()
+			.any(|x| x.severity == Severity::Error);
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = &Diagnostic> {
+		return self.diagnostics.iter();
+	}
+
+	pub fn set_location(&mut self, location: &Location) {
+		for i in &mut self.diagnostics {
+			i.set_location(location);
+		}
+	}
+
+	pub fn set_file_name(&mut self, file_name: &str) {
+		for i in &mut self.diagnostics {
+			i.set_file_name(file_name);
+		}
+	}
+
+	pub fn attach_source(&mut self, file_name: &str, source: &str) {
+		for i in &mut self.diagnostics {
+			i.attach_source(file_name, source);
+		}
+	}
+}
+
+impl From<Diagnostic> for Diagnostics {
+	fn from(diagnostic: Diagnostic) -> Self {
+		return Self {
+			diagnostics: vec![diagnostic],
+		};
+	}
+}
+
+impl From<String> for Diagnostics {
+	fn from(message: String) -> Self {
+		return Diagnostic::from(message).into();
+	}
+}
+
+impl Display for Diagnostics {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		for (i, diagnostic) in self.diagnostics.iter().enumerate() {
+			if i != 0 {
+				write!(f, "\n\n")?;
+			}
+
+			write!(f, "{}", diagnostic)?;
+		}
+
+		return Ok(());
+	}
+}
+
+impl std::error::Error for Diagnostics {}
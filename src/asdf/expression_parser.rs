@@ -1,288 +1,370 @@
 use crate::asdf::{
-	ast, location::Location, operators::Operators, parsing_context::ParsingContext, tokens::Tokens,
-	Result,
+	ast, operators::Operators, parsing_context::ParsingContext, token_cursor::TokenCursor,
+	tokens::Tokens, Result,
 };
 
-use std::collections::VecDeque;
+// a chain of operators doesn't nest while parsing, but the checker still recurses through every
+// one of them
+const MAX_OPERATORS: usize = 256;
 
-pub struct ExpressionParser {
-	tokens: VecDeque<(Tokens, Location)>,
+// stops at the first token that can't continue the expression, the caller checks what it is
+pub struct ExpressionParser<'a> {
+	tokens: &'a mut TokenCursor,
+	operators: usize,
 }
 
-impl ExpressionParser {
-	pub fn new(tokens: VecDeque<(Tokens, Location)>) -> Self {
-		Self { tokens }
+impl<'a> ExpressionParser<'a> {
+	pub fn new(tokens: &'a mut TokenCursor) -> Self {
+		Self {
+			tokens,
+			operators: 0,
+		}
 	}
 
-	pub fn parse(mut self, ctx: &mut ParsingContext) -> Result<ast::BoxedNode> {
-		// TODO: errors with location
+	pub fn parse(&mut self, ctx: &mut ParsingContext) -> Result<ast::BoxedNode> {
+		self.tokens.enter()?;
+		let ret = self.parse_nested(ctx);
+		self.tokens.leave();
 
-		assert!(!self.tokens.is_empty());
+		ret
+	}
 
-		let Some(mut lhs) = self.parse_value(ctx, 0)? else {
-			return Err(format!("cannot expression parse: {:?}", self.tokens));
+	fn parse_nested(&mut self, ctx: &mut ParsingContext) -> Result<ast::BoxedNode> {
+		let lhs = match self.parse_value(ctx) {
+			Ok(Some(x)) => x,
+			Ok(None) => return self.expected_value(),
+			Err(x) => return Err(x),
 		};
 
-		while !self.tokens.is_empty() {
-			if let Some(op) = self.parse_binary_operator(0) {
-				lhs = self.parse_binary_operation(ctx, op, lhs)?;
-			} else {
-				return Err(format!("cannot expression parse: {:?}", self.tokens));
-			}
-		}
+		self.parse_binary_operations(ctx, lhs, 0)
+			.map(Self::ensure_value)
+	}
 
-		Ok(Self::ensure_value(lhs))
+	fn expected_value<T>(&self) -> Result<T> {
+		self.tokens.peek_location().error(format!(
+			"expected a value, found {}",
+			self.tokens.describe_next()
+		))
 	}
 
-	fn parse_value(
+	// the operators of one precedence are parsed in a loop, only an operator that binds tighter
+	// than the one before it recurses, so a long chain like `1 + 1 + ...` isn't nested
+	fn parse_binary_operations(
 		&mut self,
 		ctx: &mut ParsingContext,
-		current_precedence: u8,
-	) -> Result<Option<ast::BoxedNode>> {
-		let value = self
-			.parse_scope(ctx)?
-			.or_else(|| self.parse_identifier(ctx))
-			.or_else(|| self.parse_integer())
-			.or_else(|| self.parse_string());
-
-		let Some(mut value) = value else {
-			return Ok(None);
-		};
+		mut lhs: ast::BoxedNode,
+		min_precedence: u8,
+	) -> Result<ast::BoxedNode> {
+		while let Some(op) = self.parse_binary_operator(min_precedence) {
+			let precedence = op.get_precedence();
 
-		value = self.parse_function_call(ctx, value)?;
+			self.operators += 1;
 
-		if let Some(op) = self.parse_binary_operator(current_precedence) {
-			Ok(Some(self.parse_binary_operation(ctx, op, value)?))
-		} else {
-			Ok(Some(value))
+			if self.operators > MAX_OPERATORS {
+				return self.tokens.get_location().error(format!(
+					"more than {} operators in one expression",
+					MAX_OPERATORS
+				));
+			}
+
+			let Some(mut rhs) = self.parse_value(ctx)? else {
+				return self.tokens.peek_location().error(format!(
+					"expected a value for '{op:?}', found {}",
+					self.tokens.describe_next()
+				));
+			};
+
+			// every operator is left associative, only one that binds tighter takes the rhs, and
+			// there are only so many precedences for it to recurse through
+			while let Some((next, _)) = self.peek_binary_operator() {
+				if next.get_precedence() <= precedence {
+					break;
+				}
+
+				rhs = self.parse_binary_operations(ctx, rhs, precedence + 1)?;
+			}
+
+			lhs = Self::binary_operation(op, lhs, rhs);
+		}
+
+		Ok(lhs)
+	}
+
+	fn parse_value(&mut self, ctx: &mut ParsingContext) -> Result<Option<ast::BoxedNode>> {
+		match self.parse_primary(ctx) {
+			Ok(Some(x)) => self.parse_function_call(ctx, x).map(Some),
+			x => x,
+		}
+	}
+
+	// the values are parsed one function further down, which keeps the frames that parentheses
+	// recurse through small
+	fn parse_primary(&mut self, ctx: &mut ParsingContext) -> Result<Option<ast::BoxedNode>> {
+		match self.tokens.peek() {
+			Some(Tokens::Star) => self.parse_indirection(ctx),
+			Some(Tokens::ParenOpen) => self.parse_scope(ctx),
+			_ => Ok(self.parse_literal(ctx)),
 		}
 	}
 
-	fn parse_binary_operator(&mut self, current_precedence: u8) -> Option<Operators> {
-		let (op, amount) = match self.peek() {
+	fn parse_literal(&mut self, ctx: &mut ParsingContext) -> Option<ast::BoxedNode> {
+		match self.tokens.peek() {
+			Some(Tokens::Identifier(_)) => self.parse_identifier(ctx),
+			Some(Tokens::Integer(_)) => self.parse_integer(),
+			Some(Tokens::String(_)) => self.parse_string(),
+			_ => None,
+		}
+	}
+
+	fn peek_binary_operator(&self) -> Option<(Operators, usize)> {
+		let ret = match self.tokens.peek() {
 			Some(Tokens::Plus) => (Operators::Addition, 1),
 			Some(Tokens::Minus) => (Operators::Subtraction, 1),
 			Some(Tokens::Star) => (Operators::Multiplication, 1),
 			Some(Tokens::Slash) => (Operators::Division, 1),
 			Some(Tokens::Percent) => (Operators::Modulo, 1),
-			Some(Tokens::Equals) => match self.peek_nth(1) {
+			Some(Tokens::Equals) => match self.tokens.peek_nth(1) {
 				Some(Tokens::Equals) => (Operators::Equals, 2),
 				_ => (Operators::Assignment, 1),
 			},
-			Some(Tokens::ExclamationMark) => match self.peek_nth(1) {
+			Some(Tokens::ExclamationMark) => match self.tokens.peek_nth(1) {
 				Some(Tokens::Equals) => (Operators::NotEquals, 2),
 				_ => return None,
 			},
-			Some(Tokens::And) => match self.peek_nth(1) {
+			Some(Tokens::LessThan) => match self.tokens.peek_nth(1) {
+				Some(Tokens::Equals) => (Operators::LessThanOrEqual, 2),
+				_ => (Operators::LessThan, 1),
+			},
+			Some(Tokens::GreaterThan) => match self.tokens.peek_nth(1) {
+				Some(Tokens::Equals) => (Operators::GreaterThanOrEqual, 2),
+				_ => (Operators::GreaterThan, 1),
+			},
+			Some(Tokens::And) => match self.tokens.peek_nth(1) {
 				Some(Tokens::And) => (Operators::LogicalAnd, 2),
 				_ => return None,
 			},
-			Some(Tokens::Pipe) => match self.peek_nth(1) {
+			Some(Tokens::Pipe) => match self.tokens.peek_nth(1) {
 				Some(Tokens::Pipe) => (Operators::LogicalOr, 2),
 				_ => return None,
 			},
 			_ => return None,
 		};
 
-		if op.get_precedence() < current_precedence {
+		Some(ret)
+	}
+
+	fn parse_binary_operator(&mut self, min_precedence: u8) -> Option<Operators> {
+		let (op, amount) = self.peek_binary_operator()?;
+
+		if op.get_precedence() < min_precedence {
 			return None;
 		}
 
 		for _ in 0..amount {
-			self.pop();
+			self.tokens.pop();
 		}
 
 		Some(op)
 	}
 
-	fn parse_binary_operation(
-		&mut self,
-		ctx: &mut ParsingContext,
-		op: Operators,
-		lhs: ast::BoxedNode,
-	) -> Result<ast::BoxedNode> {
-		let Some(rhs) = self.parse_value(ctx, op.get_precedence())? else {
-			return Err(format!("expected a value for '{op:?}', found nothing"));
-		};
-
+	fn binary_operation(op: Operators, lhs: ast::BoxedNode, rhs: ast::BoxedNode) -> ast::BoxedNode {
 		let lhs = match op {
 			Operators::Assignment => lhs,
 			_ => Self::ensure_value(lhs),
 		};
 
-		Ok(ast::BinaryOperation::new(op, lhs, Self::ensure_value(rhs)).into())
+		let location = lhs.get_location().to(rhs.get_location());
+		ast::BinaryOperation::new(op, lhs, Self::ensure_value(rhs), location).into()
 	}
 
-	fn parse_arguments(
-		ctx: &mut ParsingContext,
-		tokens: Vec<(Tokens, Location)>,
-	) -> Result<Vec<ast::BoxedNode>> {
-		if tokens.is_empty() {
-			return Ok(vec![]);
-		}
-
+	// after the '(', up to and including the ')'
+	fn parse_arguments(&mut self, ctx: &mut ParsingContext) -> Result<Vec<ast::BoxedNode>> {
 		let mut ret = vec![];
 
-		let mut current_tokens = vec![];
-		let mut diff = 0;
-
-		for i in tokens {
-			let typ = i.0.clone();
+		if self.tokens.peek() == Some(Tokens::ParenClose) {
+			self.tokens.pop();
+			return Ok(ret);
+		}
 
-			if typ == Tokens::ParenOpen {
-				diff += 1;
-			} else if typ == Tokens::ParenClose {
-				assert!(diff > 0);
-				diff -= 1;
-			} else if typ == Tokens::Comma && diff == 0 {
-				ret.push(Self::new(current_tokens.into()).parse(ctx)?);
-				current_tokens = Vec::new();
+		loop {
+			ret.push(self.parse(ctx)?);
 
-				continue;
+			match self.tokens.pop() {
+				Some((Tokens::Comma, _)) => {}
+				Some((Tokens::ParenClose, _)) => return Ok(ret),
+				x => return self.tokens.unexpected("',' or ')'", x),
 			}
-
-			current_tokens.push(i);
 		}
+	}
 
-		assert!(diff == 0);
+	fn parse_function_call(
+		&mut self,
+		ctx: &mut ParsingContext,
+		mut node: ast::BoxedNode,
+	) -> Result<ast::BoxedNode> {
+		loop {
+			let ret = match self.tokens.peek() {
+				Some(Tokens::ParenOpen) => self.parse_call(ctx, node),
+				Some(Tokens::BracketOpen) => self.parse_index(ctx, node),
+				Some(Tokens::Dot) => self.parse_method_call(ctx, node),
+				_ => return Ok(node),
+			};
 
-		if current_tokens.is_empty() {
-			return Err(format!("expected a value, found nothing"));
+			node = ret?;
 		}
-
-		ret.push(Self::new(current_tokens.into()).parse(ctx)?);
-		Ok(ret)
 	}
 
-	fn parse_function_call(
+	fn parse_call(
 		&mut self,
 		ctx: &mut ParsingContext,
 		node: ast::BoxedNode,
 	) -> Result<ast::BoxedNode> {
-		let Some(Tokens::ParenOpen) = self.peek() else {
-			return Ok(node);
-		};
-
-		let argument_tokens = self.pop_scope(Tokens::ParenOpen, Tokens::ParenClose)?;
-		let args = Self::parse_arguments(ctx, argument_tokens)?;
+		self.tokens.pop();
+		let args = self.parse_arguments(ctx)?;
 
-		Ok(ast::CallStatement::new(node, args).into())
+		let location = node.get_location().to(self.tokens.get_location());
+		Ok(ast::CallStatement::new(node, args, location).into())
 	}
 
-	fn parse_scope(&mut self, ctx: &mut ParsingContext) -> Result<Option<ast::BoxedNode>> {
-		let Some(Tokens::ParenOpen) = self.peek() else {
-			return Ok(None);
-		};
+	fn parse_index(
+		&mut self,
+		ctx: &mut ParsingContext,
+		node: ast::BoxedNode,
+	) -> Result<ast::BoxedNode> {
+		self.tokens.pop();
 
-		let scope_tokens = self.pop_scope(Tokens::ParenOpen, Tokens::ParenClose)?;
-		let ret = Self::new(scope_tokens.into()).parse(ctx)?;
+		let index = self.parse(ctx)?;
+		self.tokens.pop_checked(Tokens::BracketClose)?;
 
-		Ok(Some(ret))
+		let location = node.get_location().to(self.tokens.get_location());
+		Ok(ast::Index::new(Self::ensure_value(node), index, location).into())
 	}
 
-	fn parse_identifier(&mut self, ctx: &mut ParsingContext) -> Option<ast::BoxedNode> {
-		let Some(Tokens::Identifier(_)) = self.peek() else {
-			return None;
-		};
+	fn parse_method_call(
+		&mut self,
+		ctx: &mut ParsingContext,
+		node: ast::BoxedNode,
+	) -> Result<ast::BoxedNode> {
+		self.tokens.pop();
 
-		let Some((Tokens::Identifier(name), _)) = self.pop() else {
-			unreachable!();
+		let Some((Tokens::Identifier(method_name), _)) = self.tokens.pop() else {
+			return self
+				.tokens
+				.get_location()
+				.error(format!("expected a method name after '.'"));
 		};
 
-		if let Some((function, return_type, argument_types)) = ctx.find_extern_function(&name) {
-			Some(ast::ExternFunction::new(function, return_type, argument_types).into())
-		} else if let Some((value_type, offset, size)) = ctx.find_variable(&name) {
-			Some(ast::Stack::new(value_type, offset, size).into())
-		} else {
-			Some(ast::Relative::new(name).into())
+		if self.tokens.peek() != Some(Tokens::ParenOpen) {
+			return self
+				.tokens
+				.get_location()
+				.error(format!("expected '(' after '.{method_name}'"));
 		}
+
+		self.tokens.pop();
+		let args = self.parse_arguments(ctx)?;
+
+		let location = node.get_location().to(self.tokens.get_location());
+		Ok(ast::MethodCall::new(Self::ensure_value(node), method_name, args, location).into())
 	}
 
-	fn parse_integer(&mut self) -> Option<ast::BoxedNode> {
-		let Some(Tokens::Integer(_)) = self.peek() else {
-			return None;
+	fn parse_indirection(&mut self, ctx: &mut ParsingContext) -> Result<Option<ast::BoxedNode>> {
+		let Some(Tokens::Star) = self.tokens.peek() else {
+			return Ok(None);
 		};
 
-		let Some((Tokens::Integer(value), _)) = self.pop() else {
-			unreachable!();
+		let (_, start) = self.tokens.pop().unwrap();
+
+		// binds tighter than any binary operator
+		self.tokens.enter()?;
+		let value = self.parse_value(ctx);
+		self.tokens.leave();
+
+		let Some(value) = value? else {
+			return self.tokens.peek_location().error(format!(
+				"expected a value after '*', found {}",
+				self.tokens.describe_next()
+			));
 		};
 
-		Some(ast::Integer::new(value).into())
+		let location = start.to(value.get_location());
+		Ok(Some(
+			ast::Indirection::new(Self::ensure_value(value), location).into(),
+		))
 	}
 
-	fn parse_string(&mut self) -> Option<ast::BoxedNode> {
-		let Some(Tokens::String(_)) = self.peek() else {
-			return None;
-		};
+	fn parse_scope(&mut self, ctx: &mut ParsingContext) -> Result<Option<ast::BoxedNode>> {
+		self.tokens.pop();
 
-		let Some((Tokens::String(value), _)) = self.pop() else {
-			unreachable!();
-		};
+		let ret = self.parse(ctx);
 
-		Some(ast::ExternString::new(value).into())
+		if ret.is_ok() {
+			self.tokens.pop_checked(Tokens::ParenClose)?;
+		}
+
+		ret.map(Some)
 	}
 
-	fn peek(&self) -> Option<Tokens> {
-		let Some((ret, _)) = self.tokens.front() else {
+	fn parse_identifier(&mut self, ctx: &mut ParsingContext) -> Option<ast::BoxedNode> {
+		let Some(Tokens::Identifier(_)) = self.tokens.peek() else {
 			return None;
 		};
 
-		Some(ret.clone())
-	}
-
-	fn peek_nth(&self, index: usize) -> Option<Tokens> {
-		let Some((ret, _)) = self.tokens.get(index) else {
-			return None;
+		let Some((Tokens::Identifier(mut name), start)) = self.tokens.pop() else {
+			unreachable!();
 		};
 
-		Some(ret.clone())
-	}
+		// a path to a function in another module, e.g. `math::square`
+		while let (Some(Tokens::ColonColon), Some(Tokens::Identifier(x))) =
+			(self.tokens.peek(), self.tokens.peek_nth(1))
+		{
+			self.tokens.pop();
+			self.tokens.pop();
 
-	fn pop(&mut self) -> Option<(Tokens, Location)> {
-		self.tokens.pop_front()
-	}
+			name = format!("{}::{}", name, x);
+		}
 
-	fn pop_while(
-		&mut self,
-		mut predicate: impl FnMut(Option<Tokens>) -> Result<bool>,
-	) -> Result<Vec<(Tokens, Location)>> {
-		let mut ret = vec![];
+		let location = start.to(self.tokens.get_location());
 
-		while predicate(self.peek())? {
-			ret.push(self.pop().unwrap());
+		if name.contains("::") {
+			return Some(ast::Relative::new(name, location).into());
 		}
 
-		Ok(ret)
+		if let Some((value_type, offset, size)) = ctx.find_variable(&name) {
+			Some(ast::Stack::new(name, value_type, offset, size, location).into())
+		} else {
+			Some(ast::Relative::new(name, location).into())
+		}
 	}
 
-	fn pop_scope(&mut self, open: Tokens, close: Tokens) -> Result<Vec<(Tokens, Location)>> {
-		assert!(self.peek() == Some(open.clone()));
-		self.pop();
+	fn parse_integer(&mut self) -> Option<ast::BoxedNode> {
+		let Some(Tokens::Integer(_)) = self.tokens.peek() else {
+			return None;
+		};
 
-		let mut diff = 1;
-		let ret = self.pop_while(|x| {
-			let Some(x) = x else {
-				return Err(format!("expected '{close:?}', got nothing"));
-			};
+		let Some((Tokens::Integer(value), location)) = self.tokens.pop() else {
+			unreachable!();
+		};
 
-			if x == open {
-				diff += 1;
-			} else if x == close {
-				diff -= 1;
-			}
+		Some(ast::Integer::new(value, location).into())
+	}
 
-			Ok(diff > 0)
-		})?;
+	fn parse_string(&mut self) -> Option<ast::BoxedNode> {
+		let Some(Tokens::String(_)) = self.tokens.peek() else {
+			return None;
+		};
 
-		let last = self.pop();
-		assert!(last.unwrap().0 == close);
+		let Some((Tokens::String(value), location)) = self.tokens.pop() else {
+			unreachable!();
+		};
 
-		Ok(ret)
+		Some(ast::ExternString::new(value, location).into())
 	}
 
 	fn ensure_value(x: ast::BoxedNode) -> ast::BoxedNode {
 		if x.is_reference() {
-			ast::Dereference::new(x).into()
+			let location = x.get_location().clone();
+			ast::Dereference::new(x, location).into()
 		} else {
 			x
 		}
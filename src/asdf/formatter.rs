@@ -0,0 +1,310 @@
+// `asdf fmt`, writes a source file out again in the canonical style
+//
+// unlike rustfmt this isn't a printer for the ast, which doesn't keep enough of the source for
+// it, declarations and parentheses are gone by then and comments never make it in. the tokens
+// are laid out instead, with the comments kept in between them wherever they were, so a comment
+// in the middle of an expression breaks the line there and the rest of it is indented once more
+
+use crate::asdf::{
+	lexer::Lexer, location::Location, module_loader::Source, tokens::Tokens, Code, Diagnostic,
+	Result,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+	// identifiers, keywords and literals
+	Word,
+	Binary,
+	// `*` in front of a value or a type
+	Prefix,
+	Open,
+	Close,
+	GenericOpen,
+	GenericClose,
+	BlockOpen,
+	BlockClose,
+	// `,`, `;` and `:`
+	Separator,
+	// `.` and `::`
+	Joint,
+	Hash,
+	Comment,
+	DocComment,
+}
+
+// a token, or the ones written together like `==` and `->`
+struct Piece<'a> {
+	token: Tokens,
+	kind: Kind,
+	text: &'a str,
+	location: Location,
+	// where it ends, block comments and strings can go over multiple lines
+	end_line: u64,
+}
+
+impl Piece<'_> {
+	fn is_value(&self) -> bool {
+		return matches!(
+			self.token,
+			Tokens::Identifier(_)
+				| Tokens::Integer(_)
+				| Tokens::String(_)
+				| Tokens::ParenClose
+				| Tokens::BracketClose
+		);
+	}
+
+	fn is_line_comment(&self) -> bool {
+		return self.kind == Kind::Comment && self.text.starts_with("//");
+	}
+}
+
+struct Printer {
+	out: String,
+	line: String,
+	depth: usize,
+	// the parentheses and brackets that are still open, lines inside of them are indented
+	nesting: usize,
+	// the nesting an attribute's brackets started at
+	attribute: Option<usize>,
+	newline: bool,
+	// a line started now would be in the middle of a statement
+	continues: bool,
+}
+
+// only sources that parse are formatted, the result lexes to the same tokens
+pub fn format(file_name: &str, source: &str) -> Result<String> {
+	Source::parse(file_name, None, source)?;
+
+	let tokens = Lexer::new(source, file_name.into()).with_comments().lex()?;
+	let ret = print(&pieces(source, &tokens));
+
+	let formatted = Lexer::new(&ret, file_name.into()).with_comments().lex()?;
+
+	if normalize(&tokens) != normalize(&formatted) {
+		return Err(Diagnostic::error(
+			Code::FormattingChanged,
+			format!("formatting '{}' would change its tokens", file_name),
+		)
+		.into());
+	}
+
+	return Ok(ret);
+}
+
+// trailing whitespace isn't kept in comments
+fn normalize(tokens: &[(Tokens, Location)]) -> Vec<Tokens> {
+	return tokens
+		.iter()
+		.map(|(x, _)| match x {
+			Tokens::Comment(x) => Tokens::Comment(x.trim_end().into()),
+			Tokens::DocComment(x) => Tokens::DocComment(x.trim_end().into()),
+			x => x.clone(),
+		})
+		.collect();
+}
+
+fn pieces<'a>(source: &'a str, tokens: &[(Tokens, Location)]) -> Vec<Piece<'a>> {
+	let mut ret: Vec<Piece> = vec![];
+	let mut in_generics = false;
+	let mut i = 0;
+
+	while i < tokens.len() {
+		let (token, location) = &tokens[i];
+		let next = tokens.get(i + 1).map(|(x, _)| x);
+
+		// comments don't change what the operators around them mean
+		let mut previous = ret.iter().rev().filter(|x| x.kind != Kind::Comment);
+		let (last, before_last) = (previous.next(), previous.next());
+		let after_value = last.is_some_and(|x| x.is_value());
+
+		let (count, kind) = match (token, next) {
+			(Tokens::LessThan, _)
+				if last.is_some_and(|x| matches!(x.token, Tokens::Identifier(_)))
+					&& before_last.is_some_and(|x| x.token == Tokens::Fn) =>
+			{
+				in_generics = true;
+				(1, Kind::GenericOpen)
+			}
+			(Tokens::GreaterThan, _) if in_generics => {
+				in_generics = false;
+				(1, Kind::GenericClose)
+			}
+			(
+				Tokens::Equals | Tokens::ExclamationMark | Tokens::LessThan | Tokens::GreaterThan,
+				Some(Tokens::Equals),
+			) => (2, Kind::Binary),
+			(Tokens::And, Some(Tokens::And)) => (2, Kind::Binary),
+			(Tokens::Pipe, Some(Tokens::Pipe)) => (2, Kind::Binary),
+			(Tokens::Minus, Some(Tokens::GreaterThan)) => (2, Kind::Binary),
+			(Tokens::Star, _) if !after_value => (1, Kind::Prefix),
+			(
+				Tokens::Plus
+				| Tokens::Minus
+				| Tokens::Star
+				| Tokens::Slash
+				| Tokens::Percent
+				| Tokens::Equals
+				| Tokens::ExclamationMark
+				| Tokens::LessThan
+				| Tokens::GreaterThan
+				| Tokens::And
+				| Tokens::Pipe,
+				_,
+			) => (1, Kind::Binary),
+			(Tokens::ParenOpen | Tokens::BracketOpen, _) => (1, Kind::Open),
+			(Tokens::ParenClose | Tokens::BracketClose, _) => (1, Kind::Close),
+			(Tokens::CurlyOpen, _) => (1, Kind::BlockOpen),
+			(Tokens::CurlyClose, _) => (1, Kind::BlockClose),
+			(Tokens::Comma | Tokens::SemiColon | Tokens::Colon, _) => (1, Kind::Separator),
+			(Tokens::Dot | Tokens::ColonColon, _) => (1, Kind::Joint),
+			(Tokens::Hash, _) => (1, Kind::Hash),
+			(Tokens::Comment(_), _) => (1, Kind::Comment),
+			(Tokens::DocComment(_), _) => (1, Kind::DocComment),
+			_ => (1, Kind::Word),
+		};
+
+		let (_, end) = &tokens[i + count - 1];
+		let text = &source[location.get_offset()..end.get_offset() + end.get_length()];
+
+		ret.push(Piece {
+			token: token.clone(),
+			kind,
+			text,
+			location: location.clone(),
+			end_line: location.get_line_number() + text.matches('\n').count() as u64,
+		});
+
+		i += count;
+	}
+
+	return ret;
+}
+
+fn print(pieces: &[Piece]) -> String {
+	let mut printer = Printer {
+		out: String::new(),
+		line: String::new(),
+		depth: 0,
+		nesting: 0,
+		attribute: None,
+		newline: false,
+		continues: false,
+	};
+
+	let mut i = 0;
+
+	while i < pieces.len() {
+		let piece = &pieces[i];
+		let previous = i.checked_sub(1).map(|x| &pieces[x]);
+
+		let same_line = previous.is_some_and(|x| x.end_line == piece.location.get_line_number());
+		let blank_line =
+			previous.is_some_and(|x| piece.location.get_line_number() > x.end_line + 1);
+
+		match piece.kind {
+			Kind::BlockClose => {
+				printer.depth = printer.depth.saturating_sub(1);
+				printer.continues = false;
+			}
+			Kind::Close => printer.nesting = printer.nesting.saturating_sub(1),
+			_ => {}
+		}
+
+		let starts_line = match piece.kind {
+			_ if previous.is_none() => true,
+			Kind::BlockClose | Kind::DocComment => true,
+			// stays at the end of the line it was on
+			Kind::Comment => !same_line,
+			_ => printer.newline,
+		};
+
+		if starts_line {
+			printer.start_line(blank_line && piece.kind != Kind::BlockClose);
+			printer.newline = false;
+		} else if previous.is_some_and(|x| space_between(x, piece)) {
+			printer.line.push(' ');
+		}
+
+		match piece.kind {
+			Kind::Comment | Kind::DocComment => printer.line.push_str(piece.text.trim_end()),
+			_ => printer.line.push_str(piece.text),
+		}
+
+		let next = pieces.get(i + 1);
+
+		printer.continues = match piece.kind {
+			Kind::Comment => printer.continues,
+			Kind::BlockOpen | Kind::BlockClose | Kind::DocComment => false,
+			Kind::Separator if piece.token == Tokens::SemiColon => printer.nesting > 0,
+			_ => true,
+		};
+
+		match piece.kind {
+			// `{}` stays on one line
+			Kind::BlockOpen if next.is_some_and(|x| x.kind == Kind::BlockClose) => {
+				printer.line.push('}');
+				printer.newline = true;
+				i += 1;
+			}
+			Kind::BlockOpen => {
+				printer.depth += 1;
+				printer.newline = true;
+			}
+			Kind::BlockClose | Kind::DocComment => printer.newline = true,
+			Kind::Separator if piece.token == Tokens::SemiColon && printer.nesting == 0 => {
+				printer.newline = true
+			}
+			Kind::Comment if piece.is_line_comment() => printer.newline = true,
+			Kind::Comment
+				if next.is_some_and(|x| x.location.get_line_number() > piece.end_line) =>
+			{
+				printer.newline = true
+			}
+			Kind::Hash => printer.attribute = Some(printer.nesting),
+			Kind::Open => printer.nesting += 1,
+			Kind::Close if printer.attribute == Some(printer.nesting) => {
+				printer.attribute = None;
+				printer.newline = true;
+				printer.continues = false;
+			}
+			_ => {}
+		}
+
+		i += 1;
+	}
+
+	printer.start_line(false);
+	return printer.out;
+}
+
+fn space_between(previous: &Piece, next: &Piece) -> bool {
+	return match (previous.kind, next.kind) {
+		(Kind::Open | Kind::Prefix | Kind::Joint | Kind::Hash | Kind::GenericOpen, _) => false,
+		(
+			_,
+			Kind::Separator | Kind::Close | Kind::Joint | Kind::GenericOpen | Kind::GenericClose,
+		) => false,
+		// calls, indexing and the parameters after type parameters
+		(Kind::GenericClose, Kind::Open) => false,
+		(_, Kind::Open) => !previous.is_value(),
+		_ => true,
+	};
+}
+
+impl Printer {
+	fn start_line(&mut self, blank_line: bool) {
+		if !self.line.trim().is_empty() {
+			self.out += self.line.trim_end();
+			self.out.push('\n');
+		}
+
+		if blank_line && !self.out.is_empty() {
+			self.out.push('\n');
+		}
+
+		// the open parentheses already indent what's inside of them
+		let continuation = self.continues && self.nesting == 0;
+		self.line = "\t".repeat(self.depth + self.nesting + continuation as usize);
+	}
+}
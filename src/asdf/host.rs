@@ -0,0 +1,104 @@
+// rust functions that asdf code can call into
+
+use crate::asdf::{runtime, tc};
+
+// a rust type with the same representation as an asdf type
+pub trait IntoAsdfType {
+	fn asdf_type() -> tc::Type;
+}
+
+impl IntoAsdfType for () {
+	fn asdf_type() -> tc::Type {
+		return tc::Type::None;
+	}
+}
+
+impl IntoAsdfType for i64 {
+	fn asdf_type() -> tc::Type {
+		return tc::Type::I64;
+	}
+}
+
+impl IntoAsdfType for *const runtime::Str {
+	fn asdf_type() -> tc::Type {
+		return tc::Type::Str;
+	}
+}
+
+impl IntoAsdfType for *const u8 {
+	fn asdf_type() -> tc::Type {
+		return tc::Type::Pointer(Box::new(tc::Type::Char));
+	}
+}
+
+impl IntoAsdfType for *const *const u8 {
+	fn asdf_type() -> tc::Type {
+		return tc::Type::Pointer(Box::new(<*const u8>::asdf_type()));
+	}
+}
+
+impl IntoAsdfType for *mut i64 {
+	fn asdf_type() -> tc::Type {
+		return tc::Type::Pointer(Box::new(tc::Type::I64));
+	}
+}
+
+// an `extern "C" fn` pointer whose signature only uses types asdf knows about, function items
+// need to be cast first, e.g. `f as extern "C" fn(_) -> _`
+pub trait HostFunction {
+	fn get_address(&self) -> u64;
+	fn get_return_type() -> tc::Type;
+	fn get_argument_types() -> Vec<tc::Type>;
+}
+
+// a tuple of arguments that rust code can pass to an asdf function
+pub trait HostArguments {
+	fn get_argument_types() -> Vec<tc::Type>;
+
+	/// # Safety
+	/// `address` has to point to a function taking these arguments and returning `R`
+	unsafe fn call<R>(self, address: *const u8) -> R;
+}
+
+macro_rules! signature {
+	($($argument:ident $name:ident),*) => {
+		impl<R: IntoAsdfType, $($argument: IntoAsdfType),*> HostFunction
+			for extern "C" fn($($argument),*) -> R
+		{
+			fn get_address(&self) -> u64 {
+				return *self as usize as u64;
+			}
+
+			fn get_return_type() -> tc::Type {
+				return R::asdf_type();
+			}
+
+			fn get_argument_types() -> Vec<tc::Type> {
+				return vec![$($argument::asdf_type()),*];
+			}
+		}
+
+		impl<$($argument: IntoAsdfType),*> HostArguments for ($($argument,)*) {
+			fn get_argument_types() -> Vec<tc::Type> {
+				return vec![$($argument::asdf_type()),*];
+			}
+
+			unsafe fn call<R>(self, address: *const u8) -> R {
+				let ($($name,)*) = self;
+
+				let function: extern "C" fn($($argument),*) -> R =
+					unsafe { std::mem::transmute(address) };
+
+				return function($($name),*);
+			}
+		}
+	};
+}
+
+signature!();
+signature!(A a);
+signature!(A a, B b);
+signature!(A a, B b, C c);
+signature!(A a, B b, C c, D d);
+signature!(A a, B b, C c, D d, E e);
+signature!(A a, B b, C c, D d, E e, F f);
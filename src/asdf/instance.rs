@@ -1,148 +1,460 @@
+use std::{cell::RefCell, collections::HashMap, ffi::CString, os::unix::ffi::OsStrExt};
+
 use crate::asdf::{
 	ast::{self, Node},
 	byte_code,
-	lexer::Lexer,
-	parser::Parser,
-	parsing_context::ParsingContext,
-	tc, Result,
+	compiled_module::CompiledModule,
+	lint,
+	module_loader::{self, Source},
+	runtime, stdlib, tc, Code, Diagnostic, Diagnostics, HostArguments, HostFunction, IntoAsdfType,
+	MemoryProvider, Result, SourceProvider,
 };
 
 pub struct Instance {
-	instructions: Vec<byte_code::Instruction>,
-	main_offset: usize,
+	sources: Vec<Source>,
+	source_provider: Box<dyn SourceProvider>,
+	// the compiled sources first, followed by what `extend` and `evaluate` added on top of them
+	modules: Vec<CompiledModule>,
+	literals: Vec<runtime::OwnedStr>,
+	runtime: RefCell<runtime::Runtime>,
+	// the module each function is in, along with its offset
+	functions: HashMap<String, (usize, usize)>,
+	// `None` until something has been compiled
+	type_checking_context: Option<tc::Context>,
+	host_functions: Vec<(String, u64, tc::Type, Vec<tc::Type>)>,
+	warnings: Diagnostics,
 }
 
 impl Instance {
 	pub fn new() -> Self {
+		let mut ret = Self::without_stdlib();
+		stdlib::define(&mut ret);
+
+		return ret;
+	}
+
+	// only has the functions registered with `register_fn`
+	pub fn without_stdlib() -> Self {
 		return Self {
-			instructions: vec![],
-			main_offset: usize::default(),
+			sources: vec![],
+			source_provider: Box::new(MemoryProvider::new()),
+			modules: vec![],
+			literals: vec![],
+			runtime: RefCell::default(),
+			functions: HashMap::new(),
+			type_checking_context: None,
+			host_functions: vec![],
+			warnings: Diagnostics::new(),
 		};
 	}
 
+	// makes a rust function callable from asdf code, has to be done before parsing
+	pub fn register_fn<F: HostFunction>(&mut self, name: &str, function: F) {
+		self.host_functions.push((
+			name.into(),
+			function.get_address(),
+			F::get_return_type(),
+			F::get_argument_types(),
+		));
+	}
+
+	// a single source file, compiled right away
 	pub fn parse(&mut self, string: &str) -> Result<()> {
-		assert!(self.instructions.is_empty());
+		self.add_source("main.asdf", string)?;
+		return self.compile();
+	}
 
-		let tokens = Lexer::new(string.into(), "file_name.ext".into()).lex()?;
+	// sources can keep being added after compiling, the next compile includes them as well
+	pub fn add_source(&mut self, file_name: &str, string: &str) -> Result<()> {
+		self.sources.push(Source::parse(file_name, None, string)?);
+		return Ok(());
+	}
 
-		let mut tcc = tc::Context::new();
-		let mut ctx = ParsingContext::new(&mut tcc);
+	pub fn remove_source(&mut self, file_name: &str) {
+		self.sources.retain(|x| x.get_file_name() != file_name);
+	}
 
-		extern "C" fn print(x: *const u8) {
-			let str = unsafe { std::ffi::CStr::from_ptr(x as *const i8) }
-				.to_str()
-				.unwrap();
+	// where the modules named in `import` statements are loaded from
+	pub fn set_source_provider<T: SourceProvider + 'static>(&mut self, source_provider: T) {
+		self.source_provider = Box::new(source_provider);
+	}
 
-			print!("{}", str);
-		}
+	// only parses and type checks the added sources, along with the modules they import
+	pub fn check(&mut self) -> Result<()> {
+		self.type_check()?;
+		return Ok(());
+	}
 
-		extern "C" fn to_string(x: i64) -> *const u8 {
-			let ptr = Box::leak(Box::new(x.to_string() + "\x00")).as_ptr();
-			return ptr;
-		}
+	// the lints reported by the last successful check or compile
+	pub fn get_warnings(&self) -> &Diagnostics {
+		return &self.warnings;
+	}
+
+	// the doc comment of a function or a method, by the name it's called with
+	pub fn get_doc(&self, function_name: &str) -> Option<&str> {
+		let nodes = self.sources.iter().flat_map(|x| x.get_scope().get_nodes());
 
-		#[naked]
-		extern "C" fn bp() {
-			unsafe {
-				std::arch::asm!("int3", "ret", options(noreturn));
+		for node in nodes {
+			let function = match node {
+				ast::BoxedNode::Function(x) => x,
+				ast::BoxedNode::Implementation(x) => {
+					match x.get_functions().find(|x| x.get_name() == function_name) {
+						Some(x) => x,
+						None => continue,
+					}
+				}
+				_ => continue,
+			};
+
+			if function.get_name() == function_name {
+				return function.get_doc();
 			}
 		}
 
-		ctx.extern_function(
-			"print",
-			print as u64,
-			tc::Type::None,
-			vec![tc::Type::Pointer(Box::new(tc::Type::Char))],
-		);
+		return None;
+	}
 
-		ctx.extern_function(
-			"to_string",
-			to_string as u64,
-			tc::Type::Pointer(Box::new(tc::Type::Char)),
-			vec![tc::Type::I64],
-		);
+	// type checks all of the added sources together, then emits each of them on its own and
+	// links them into one module, which replaces everything compiled before
+	pub fn compile(&mut self) -> Result<()> {
+		let (tcc, scopes, instances) = self.type_check()?;
 
-		ctx.extern_function("bp", bp as u64, tc::Type::None, vec![]);
+		let mut ctx = Self::emit(scopes, &instances)?;
+		let module = Self::link(&mut ctx, |_| None)?;
 
-		let nodes = Parser::new(tokens.into()).parse(&mut ctx)?;
-		let global_scope = ast::Scope::new(nodes);
+		self.modules.clear();
+		self.literals.clear();
+		self.functions.clear();
 
-		global_scope.pre_type_check(&mut tcc);
-		global_scope.type_check(&mut tcc)?;
+		self.push_module(module, &mut ctx);
+		self.type_checking_context = Some(tcc);
 
-		let mut ctx = byte_code::Context::new();
-		global_scope.emit(&mut ctx);
+		return Ok(());
+	}
 
-		assert!(ctx.get_stack_size() == 0);
-		self.instructions = ctx.instructions();
+	// adds a source on top of what has been compiled, without checking or emitting the others
+	// again, the sources added since the last compile are only included by compiling again
+	pub fn extend(&mut self, file_name: &str, string: &str) -> Result<()> {
+		let source = Source::parse(file_name, None, string)?;
+		let mut tcc = self.compiled_context()?.clone();
 
-		let Some(main_offset) = ctx.get_function_offset("main") else {
-			return Err(format!("cannot find 'main' function"));
-		};
+		let start = self.sources.len();
+		self.sources.push(source);
+
+		// a definition that doesn't compile is dropped, it's checked on a copy of the context
+		// so it won't leave anything behind
+		if let Err(x) = self.append(start, &mut tcc) {
+			self.sources.truncate(start);
+			return Err(x);
+		}
 
-		self.main_offset = main_offset;
+		self.type_checking_context = Some(tcc);
 		return Ok(());
 	}
 
-	pub fn execute(&self) -> i64 {
-		assert!(!self.instructions.is_empty());
-		let mut ins = vec![];
+	// checks and emits the sources from `start` on, `tcc` has everything before them
+	fn append(&mut self, start: usize, tcc: &mut tc::Context) -> Result<()> {
+		module_loader::load_imports(self.source_provider.as_ref(), &mut self.sources)?;
 
-		for i in &self.instructions {
-			ins.extend(i.get_instruction());
+		let (scopes, instances) = Self::check_sources(tcc, &self.sources[start..])?;
+
+		let mut ctx = Self::emit(scopes, &instances)?;
+		let module = Self::link(&mut ctx, |x| self.get_address(x))?;
+
+		self.push_module(module, &mut ctx);
+		return Ok(());
+	}
+
+	// the context of what has been compiled, the added sources are compiled if nothing has
+	fn compiled_context(&mut self) -> Result<&tc::Context> {
+		if self.type_checking_context.is_none() {
+			self.compile()?;
 		}
 
-		let size = ins.len();
-		let ptr = Self::mmap(size);
+		return self.type_checking_context.as_ref().ok_or_else(|| {
+			Diagnostic::error(
+				Code::NothingCompiled,
+				format!("nothing has been compiled yet"),
+			)
+			.into()
+		});
+	}
 
-		let ret = unsafe {
-			std::ptr::copy(ins.as_ptr(), ptr, size);
+	fn emit(scopes: Vec<ast::Scope>, instances: &[ast::Function]) -> Result<byte_code::Context> {
+		let mut ctx = byte_code::Context::new();
 
-			let function: unsafe extern "C" fn() -> i64 =
-				std::mem::transmute(ptr.add(self.main_offset));
+		for i in scopes {
+			let mut file_ctx = byte_code::Context::new();
+			i.emit(&mut file_ctx);
 
-			function()
-		};
+			assert!(file_ctx.get_stack_size() == 0);
+			ctx.link(file_ctx)?;
+		}
 
-		Self::munmap(ptr, size);
-		return ret;
+		for i in instances {
+			i.emit(&mut ctx);
+		}
+
+		assert!(ctx.get_stack_size() == 0);
+		return Ok(ctx);
+	}
+
+	// functions that aren't emitted into `ctx` are called through the address `find` has for them
+	fn link(
+		ctx: &mut byte_code::Context,
+		find: impl Fn(&str) -> Option<*const u8>,
+	) -> Result<Option<CompiledModule>> {
+		ctx.link_addresses(|x| find(x).map(|x| x as u64))?;
+		let instructions = ctx.instructions()?;
+
+		if instructions.iter().all(|x| x.get_instruction().is_empty()) {
+			return Ok(None);
+		}
+
+		return Ok(Some(CompiledModule::new(&instructions)?));
+	}
+
+	fn push_module(&mut self, module: Option<CompiledModule>, ctx: &mut byte_code::Context) {
+		let index = self.modules.len();
+
+		self.modules.extend(module);
+		self.literals.extend(ctx.take_literals());
+
+		self.functions.extend(
+			ctx.get_function_offsets()
+				.into_iter()
+				.map(|(name, offset)| (name, (index, offset))),
+		);
 	}
 
-	extern "C" fn mmap(size: usize) -> *mut u8 {
-		let mut ret;
-
-		unsafe {
-			std::arch::asm!(
-				"syscall",
-				inlateout("rax") 9u64 => ret,
-				inout("rdi") 0 => _,
-				inout("rsi") size => _,
-				inout("rdx") 7 => _,
-				inout("r10") 0x22 => _,
-				inout("r8") 0 => _,
-				inout("r9") 0 => _,
-				out("rcx") _,
-				out("r11") _,
+	// the sources are left untouched, so they can be checked again once more are added
+	fn type_check(&mut self) -> Result<(tc::Context, Vec<ast::Scope>, Vec<ast::Function>)> {
+		module_loader::load_imports(self.source_provider.as_ref(), &mut self.sources)?;
+
+		let mut tcc = tc::Context::new();
+
+		for (name, address, return_type, argument_types) in &self.host_functions {
+			tcc.extern_function(
+				name.clone(),
+				*address,
+				return_type.clone(),
+				argument_types.clone(),
 			);
 		}
 
+		runtime::define(&mut tcc);
+
+		let (scopes, instances) = Self::check_sources(&mut tcc, &self.sources)?;
+
+		// the lints need every source, so they're only run here
+		let mut warnings = lint::check(&scopes);
+
+		for i in &self.sources {
+			warnings.attach_source(i.get_file_name(), i.get_text());
+		}
+
+		self.warnings = warnings;
+		return Ok((tcc, scopes, instances));
+	}
+
+	fn check_sources(
+		tcc: &mut tc::Context,
+		sources: &[Source],
+	) -> Result<(Vec<ast::Scope>, Vec<ast::Function>)> {
+		for i in sources {
+			i.define_module(tcc);
+			i.get_scope().pre_type_check(tcc);
+		}
+
+		let mut scopes = vec![];
+		let mut diagnostics = Diagnostics::new();
+
+		if let Err(x) = tcc.check_main() {
+			diagnostics.extend(x);
+		}
+
+		for i in sources {
+			let mut scope = i.get_scope().clone();
+
+			if let Err(x) = scope.type_check(tcc) {
+				tcc.report(x);
+			}
+
+			let mut reported = tcc.take_diagnostics();
+			reported.set_file_name(i.get_file_name());
+			diagnostics.extend(reported);
+
+			scopes.push(scope);
+		}
+
+		let instances = tcc.check_instances()?;
+		diagnostics.extend(tcc.take_diagnostics());
+
+		for i in sources {
+			diagnostics.attach_source(i.get_file_name(), i.get_text());
+		}
+
+		if diagnostics.has_errors() {
+			return Err(diagnostics);
+		}
+
+		return Ok((scopes, instances));
+	}
+
+	// a hex dump of the generated code, one instruction per line and labelled by function
+	pub fn dump_hex(&self) -> String {
+		let mut labels: Vec<_> = self.functions.iter().map(|(x, y)| (*y, x)).collect();
+		labels.sort();
+
+		let mut labels = labels.into_iter().peekable();
+		let mut ret = String::new();
+
+		for (index, module) in self.modules.iter().enumerate() {
+			for (offset, bytes) in module.get_instructions() {
+				if bytes.is_empty() {
+					continue;
+				}
+
+				while let Some((_, name)) = labels.next_if(|(x, _)| *x <= (index, offset)) {
+					ret += &format!("{}:\n", name);
+				}
+
+				let bytes = bytes
+					.iter()
+					.map(|x| format!("{:02x}", x))
+					.collect::<Vec<_>>()
+					.join(" ");
+
+				ret += &format!("\t{:08x}  {}\n", offset, bytes);
+			}
+		}
+
 		return ret;
 	}
 
-	extern "C" fn munmap(ptr: *const u8, size: usize) {
-		unsafe {
-			std::arch::asm!(
-				"syscall",
-				inlateout("rax") 11u64 => _,
-				inout("rdi") ptr => _,
-				inout("rsi") size => _,
-				out("rdx") _,
-				out("r10") _,
-				out("r8") _,
-				out("r9") _,
-				out("rcx") _,
-				out("r11") _,
-			);
+	// `main` gets the arguments along with the environment of this process, the way a c program
+	// would, it's fine to pass them even when it doesn't take any
+	pub fn execute(&self, arguments: &[&str]) -> Result<i64> {
+		let Some(address) = self.get_address("main") else {
+			return Err(Diagnostic::error(
+				Code::MissingMain,
+				format!("cannot find 'main' function"),
+			)
+			.into());
+		};
+
+		let arguments = Self::c_strings(arguments.iter().map(|x| x.as_bytes().to_vec()))?;
+
+		let environment = Self::c_strings(
+			std::env::vars_os()
+				.map(|(key, value)| [key.as_bytes(), b"=", value.as_bytes()].concat()),
+		)?;
+
+		let argv = Self::null_terminated(&arguments);
+		let envp = Self::null_terminated(&environment);
+
+		return runtime::Runtime::run(&self.runtime, || unsafe {
+			(arguments.len() as i64, argv.as_ptr(), envp.as_ptr()).call(address)
+		});
+	}
+
+	fn c_strings(values: impl Iterator<Item = Vec<u8>>) -> Result<Vec<CString>> {
+		return values
+			.map(|x| CString::new(x).map_err(|x| x.to_string().into()))
+			.collect();
+	}
+
+	fn null_terminated(values: &[CString]) -> Vec<*const u8> {
+		return values
+			.iter()
+			.map(|x| x.as_ptr() as *const u8)
+			.chain([std::ptr::null()])
+			.collect();
+	}
+
+	// evaluates a single expression on top of everything compiled so far, returning its raw
+	// value along with its type
+	pub fn evaluate(&mut self, expression: &str) -> Result<(i64, tc::Type)> {
+		const FILE_NAME: &str = "<expression>";
+		const FUNCTION_NAME: &str = "__evaluate";
+
+		let source = Source::parse_expression(FILE_NAME, FUNCTION_NAME, expression)?;
+		self.compiled_context()?;
+
+		let start = self.sources.len();
+		self.sources.push(source);
+
+		// an expression can't define anything but its own function, so it's checked against the
+		// context itself rather than a copy
+		let mut tcc = self
+			.type_checking_context
+			.take()
+			.unwrap_or_else(tc::Context::new);
+		let ret = self.append(start, &mut tcc);
+		let return_type = tcc.find_function(FUNCTION_NAME);
+
+		// so the next expression can define it again
+		tcc.forget_function(FUNCTION_NAME);
+
+		self.type_checking_context = Some(tcc);
+		self.sources.truncate(start);
+
+		ret?;
+
+		// only its code is kept, for the values it returned that point into it
+		let function = self.functions.remove(FUNCTION_NAME);
+
+		let (Some((return_type, _)), Some((module, offset))) = (return_type, function) else {
+			return Err(Diagnostic::error(
+				Code::UndefinedReference,
+				format!("'{}' wasn't linked", FUNCTION_NAME),
+			)
+			.into());
+		};
+
+		let address = self.modules[module].get_address(offset);
+		let ret = runtime::Runtime::run(&self.runtime, || unsafe { ().call(address) })?;
+		return Ok((ret, return_type));
+	}
+
+	// calls a function by name, the types have to match the ones it was defined with
+	pub fn call<A: HostArguments, R: IntoAsdfType>(
+		&self,
+		function_name: &str,
+		arguments: A,
+	) -> Result<R> {
+		let (Some((return_type, argument_types)), Some(address)) = (
+			self.type_checking_context
+				.as_ref()
+				.and_then(|x| x.find_function(function_name)),
+			self.get_address(function_name),
+		) else {
+			return Err(Diagnostic::error(
+				Code::UnknownFunction,
+				format!("cannot find function '{}'", function_name),
+			)
+			.into());
+		};
+
+		let expected = tc::Type::Function(Box::new(return_type), argument_types);
+		let got = tc::Type::Function(Box::new(R::asdf_type()), A::get_argument_types());
+
+		if expected != got {
+			return Err(Diagnostic::error(
+				Code::TypeMismatch,
+				format!(
+					"'{}' has the type '{}', but was called as '{}'",
+					function_name, expected, got
+				),
+			)
+			.into());
 		}
+
+		return runtime::Runtime::run(&self.runtime, || unsafe { arguments.call(address) });
+	}
+
+	fn get_address(&self, function_name: &str) -> Option<*const u8> {
+		let (module, offset) = self.functions.get(function_name)?;
+		return Some(self.modules[*module].get_address(*offset));
 	}
 }
@@ -2,31 +2,53 @@ use crate::asdf::Result;
 
 use super::{location::Location, tokens::Tokens};
 
-pub struct Lexer {
-	string: String,
+// a cursor over the source, everything before `offset` has been turned into tokens already
+pub struct Lexer<'a> {
+	string: &'a str,
 	file_name: String,
 	line_number: u64,
+	column: u64,
+	offset: usize,
+	// where the current token started
+	start: (u64, u64, usize),
 	tokens: Vec<(Tokens, Location)>,
+	// only the formatter needs them, the parser doesn't know what to do with them
+	keep_comments: bool,
 }
 
-impl Lexer {
-	pub fn new(string: String, file_name: String) -> Self {
+impl<'a> Lexer<'a> {
+	pub fn new(string: &'a str, file_name: String) -> Self {
 		return Self {
 			string,
 			file_name,
 			line_number: 1,
+			column: 1,
+			offset: 0,
+			start: (1, 1, 0),
 			tokens: vec![],
+			keep_comments: false,
 		};
 	}
 
+	pub fn with_comments(mut self) -> Self {
+		self.keep_comments = true;
+		return self;
+	}
+
 	pub fn lex(mut self) -> Result<Vec<(Tokens, Location)>> {
 		loop {
 			self.remove_white_space()?;
 
-			if self.string.is_empty() {
+			if self.rest().is_empty() {
 				break;
 			}
 
+			self.start = (self.line_number, self.column, self.offset);
+
+			if self.comments()? {
+				continue;
+			}
+
 			if self.key_words() {
 				continue;
 			}
@@ -47,15 +69,14 @@ impl Lexer {
 				continue;
 			}
 
-			Location::new(self.file_name.clone(), self.line_number)
-				.error(format!("cannot tokenize: {}", self.string))?;
+			self.error(format!("cannot tokenize '{}'", self.peek().unwrap()))?;
 		}
 
 		return Ok(self.tokens);
 	}
 
 	fn remove_white_space(&mut self) -> Result<()> {
-		let chars = self.pop_while(|x, _| {
+		self.pop_while(|x, _| {
 			let Some(c) = x else {
 				return Ok(false);
 			};
@@ -63,13 +84,77 @@ impl Lexer {
 			return Ok(c.is_whitespace());
 		})?;
 
-		for c in chars.chars() {
-			if c == '\n' {
-				self.line_number += 1;
+		return Ok(());
+	}
+
+	// `///` is kept for the function after it, `////` and longer are just comments
+	fn comments(&mut self) -> Result<bool> {
+		let rest = self.rest();
+
+		if rest.starts_with("///") && !rest.starts_with("////") {
+			for _ in 0..3 {
+				self.pop();
 			}
+
+			let text = self.pop_while(|x, _| Ok(x.is_some_and(|c| c != '\n')))?;
+			let text = text.strip_suffix('\r').unwrap_or(text);
+
+			self.push(Tokens::DocComment(
+				text.strip_prefix(' ').unwrap_or(text).into(),
+			));
+
+			return Ok(true);
 		}
 
-		return Ok(());
+		if rest.starts_with("//") {
+			let text = self.pop_while(|x, _| Ok(x.is_some_and(|c| c != '\n')))?;
+
+			if self.keep_comments {
+				self.push(Tokens::Comment(text.into()));
+			}
+
+			return Ok(true);
+		}
+
+		if !rest.starts_with("/*") {
+			return Ok(false);
+		}
+
+		// block comments nest, so commenting out code that has one in it works
+		let mut depth = 0;
+
+		loop {
+			let rest = self.rest();
+
+			if rest.starts_with("/*") {
+				depth += 1;
+			} else if rest.starts_with("*/") {
+				depth -= 1;
+			} else if rest.is_empty() {
+				let (line_number, column, offset) = self.start;
+
+				return Location::new(self.file_name.clone(), line_number)
+					.with_span(column, offset, 2)
+					.error(format!("unterminated block comment"));
+			} else {
+				self.pop();
+				continue;
+			}
+
+			self.pop();
+			self.pop();
+
+			if depth != 0 {
+				continue;
+			}
+
+			if self.keep_comments {
+				let text = &self.string[self.start.2..self.offset];
+				self.push(Tokens::Comment(text.into()));
+			}
+
+			return Ok(true);
+		}
 	}
 
 	fn key_words(&mut self) -> bool {
@@ -81,7 +166,14 @@ impl Lexer {
 			"imm" => (Tokens::Imm, 3),
 			"while" => (Tokens::While, 5),
 			"fn" => (Tokens::Fn, 2),
+			"trait" => (Tokens::Trait, 5),
+			"impl" => (Tokens::Impl, 4),
+			"for" => (Tokens::For, 3),
+			"import" => (Tokens::Import, 6),
+			"pub" => (Tokens::Pub, 3),
 			"i64" => (Tokens::I64, 3),
+			"char" => (Tokens::Char, 4),
+			"str" => (Tokens::Str, 3),
 			_ => return false,
 		};
 
@@ -94,10 +186,19 @@ impl Lexer {
 	}
 
 	fn symbols(&mut self) -> bool {
+		if self.rest().starts_with("::") {
+			self.pop();
+			self.pop();
+
+			self.push(Tokens::ColonColon);
+			return true;
+		}
+
 		let token = match self.peek().unwrap() {
 			';' => Tokens::SemiColon,
 			':' => Tokens::Colon,
 			',' => Tokens::Comma,
+			'.' => Tokens::Dot,
 			'+' => Tokens::Plus,
 			'-' => Tokens::Minus,
 			'*' => Tokens::Star,
@@ -107,10 +208,15 @@ impl Lexer {
 			'%' => Tokens::Percent,
 			'&' => Tokens::And,
 			'|' => Tokens::Pipe,
+			'#' => Tokens::Hash,
 			'(' => Tokens::ParenOpen,
 			')' => Tokens::ParenClose,
 			'{' => Tokens::CurlyOpen,
 			'}' => Tokens::CurlyClose,
+			'[' => Tokens::BracketOpen,
+			']' => Tokens::BracketClose,
+			'<' => Tokens::LessThan,
+			'>' => Tokens::GreaterThan,
 			_ => return false,
 		};
 
@@ -120,20 +226,64 @@ impl Lexer {
 		return true;
 	}
 
+	// `255`, `0xff`, `0o377`, `0b1111_1111` or `255i64`, there's only one integer type so far,
+	// so it's the only suffix
 	fn numbers(&mut self) -> Result<bool> {
+		if !self.peek().is_some_and(|x| x.is_ascii_digit()) {
+			return Ok(false);
+		}
+
 		let value = self.pop_while(|x, _| {
 			let Some(c) = x else {
 				return Ok(false);
 			};
 
-			return Ok(c.is_ascii_digit());
+			return Ok(c.is_ascii_alphanumeric() || c == '_');
 		})?;
 
-		if value.is_empty() {
-			return Ok(false);
+		let (radix, name, digits) = match value.get(..2) {
+			Some("0x") => (16, "hexadecimal", &value[2..]),
+			Some("0o") => (8, "octal", &value[2..]),
+			Some("0b") => (2, "binary", &value[2..]),
+			_ => (10, "decimal", value),
+		};
+
+		// neither 'i' nor 'u' is a digit in any of them
+		let (digits, suffix) = digits.split_at(digits.find(['i', 'u']).unwrap_or(digits.len()));
+		let digits = digits.replace('_', "");
+
+		if let Some(x) = digits.chars().find(|x| !x.is_digit(radix)) {
+			return self.error_from(
+				self.start,
+				format!("invalid digit '{}' in a {} literal", x, name),
+			);
+		}
+
+		if digits.is_empty() {
+			return self.error_from(
+				self.start,
+				format!("expected digits after '{}'", &value[..2]),
+			);
 		}
 
-		self.push(Tokens::Integer(value.parse().unwrap()));
+		if !matches!(suffix, "" | "i64") {
+			return self.error_from(
+				self.start,
+				format!(
+					"unsupported integer suffix '{}', 'i64' is the only integer type",
+					suffix
+				),
+			);
+		}
+
+		let Ok(value) = i64::from_str_radix(&digits, radix) else {
+			return self.error_from(
+				self.start,
+				"integer literal is out of range for 'i64'".into(),
+			);
+		};
+
+		self.push(Tokens::Integer(value));
 		return Ok(true);
 	}
 
@@ -145,16 +295,16 @@ impl Lexer {
 
 		self.pop();
 
-		let value = self.pop_while(|x, _| {
-			let Some(c) = x else {
-				return Err(format!("expected '\"', got nothing"));
-			};
-
-			return Ok(c != '"');
-		})?;
+		let mut value = String::new();
 
-		// TODO: actual escaped characters
-		let value = value.replace("\\n", "\n");
+		loop {
+			match self.peek() {
+				None => return self.error(format!("expected '\"', got nothing")),
+				Some('"') => break,
+				Some('\\') => value.push(self.escape_sequence()?),
+				Some(_) => value.push(self.pop()),
+			}
+		}
 
 		self.pop();
 		self.push(Tokens::String(value));
@@ -162,6 +312,90 @@ impl Lexer {
 		return Ok(true);
 	}
 
+	// errors point to the whole escape sequence, starting from the backslash
+	fn escape_sequence(&mut self) -> Result<char> {
+		let start = (self.line_number, self.column, self.offset);
+		self.pop();
+
+		let Some(c) = self.peek() else {
+			return self.error_from(start, format!("expected an escape sequence, got nothing"));
+		};
+
+		self.pop();
+
+		return match c {
+			'n' => Ok('\n'),
+			't' => Ok('\t'),
+			'r' => Ok('\r'),
+			'0' => Ok('\0'),
+			'\\' => Ok('\\'),
+			'"' => Ok('"'),
+			'\'' => Ok('\''),
+			'x' => {
+				let mut digits = String::new();
+
+				for _ in 0..2 {
+					match self.peek() {
+						Some(x) if x.is_ascii_hexdigit() => digits.push(self.pop()),
+						x => {
+							return self.error_from(
+								start,
+								format!("expected a hex digit in '\\x' escape, got {:?}", x),
+							)
+						}
+					}
+				}
+
+				let value = u8::from_str_radix(&digits, 16).unwrap();
+
+				if !value.is_ascii() {
+					return self.error_from(
+						start,
+						format!("'\\x{}' is out of range, expected at most 7f", digits),
+					);
+				}
+
+				Ok(value as char)
+			}
+			'u' => {
+				if self.peek() != Some('{') {
+					return self.error_from(start, format!("expected '{{' after '\\u'"));
+				}
+
+				self.pop();
+
+				let digits = self.pop_while(|x, _| {
+					let Some(c) = x else {
+						return Ok(false);
+					};
+
+					return Ok(c.is_ascii_hexdigit());
+				})?;
+
+				if self.peek() != Some('}') {
+					return self
+						.error_from(start, format!("expected '}}' to close '\\u{{{}'", digits));
+				}
+
+				self.pop();
+
+				let Some(value) = u32::from_str_radix(digits, 16)
+					.ok()
+					.filter(|_| digits.len() <= 6)
+					.and_then(char::from_u32)
+				else {
+					return self.error_from(
+						start,
+						format!("'\\u{{{}}}' is not a valid unicode character", digits),
+					);
+				};
+
+				Ok(value)
+			}
+			x => self.error_from(start, format!("unknown escape sequence '\\{}'", x)),
+		};
+	}
+
 	fn identifiers(&mut self) -> Result<bool> {
 		let value = self.pop_while(|x, is_first| {
 			let Some(c) = x else {
@@ -187,51 +421,83 @@ impl Lexer {
 			return Ok(false);
 		}
 
-		self.push(Tokens::Identifier(value));
+		self.push(Tokens::Identifier(value.into()));
 		return Ok(true);
 	}
 
-	fn peek(&self) -> Option<char> {
-		return self.string.chars().next();
+	fn rest(&self) -> &'a str {
+		return &self.string[self.offset..];
 	}
 
-	fn peek_word(&self) -> &str {
-		let mut size = usize::default();
+	fn peek(&self) -> Option<char> {
+		return self.rest().chars().next();
+	}
 
-		for c in self.string.chars() {
-			// TODO: is this good?
-			if c.is_ascii_alphanumeric() || c == '_' {
-				size += 1;
-				continue;
-			}
+	fn peek_word(&self) -> &'a str {
+		let rest = self.rest();
 
-			break;
-		}
+		// only ascii characters are part of words, so the index is also the size in bytes
+		let size = rest
+			.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+			.unwrap_or(rest.len());
 
-		return &self.string[0..size];
+		return &rest[..size];
 	}
 
 	fn pop(&mut self) -> char {
-		return self.string.remove(0);
+		let c = self.peek().unwrap();
+		self.offset += c.len_utf8();
+
+		if c == '\n' {
+			self.line_number += 1;
+			self.column = 1;
+		} else {
+			self.column += 1;
+		}
+
+		return c;
 	}
 
 	fn pop_while<T: Fn(Option<char>, bool) -> Result<bool>>(
 		&mut self,
 		predicate: T,
-	) -> Result<String> {
-		let mut ret = String::new();
+	) -> Result<&'a str> {
+		let start = self.offset;
 
-		while predicate(self.string.chars().next(), ret.is_empty())? {
-			ret.push(self.pop());
+		while predicate(self.peek(), self.offset == start)? {
+			self.pop();
 		}
 
-		return Ok(ret);
+		return Ok(&self.string[start..self.offset]);
+	}
+
+	// points at the character that couldn't be lexed
+	fn error<T>(&self, message: String) -> Result<T> {
+		let length = self.peek().map(|x| x.len_utf8()).unwrap_or(0);
+
+		return Location::new(self.file_name.clone(), self.line_number)
+			.with_span(self.column, self.offset, length)
+			.error(message);
+	}
+
+	fn error_from<T>(&self, start: (u64, u64, usize), message: String) -> Result<T> {
+		let (line_number, column, offset) = start;
+
+		return Location::new(self.file_name.clone(), line_number)
+			.with_span(column, offset, self.offset - offset)
+			.error(message);
 	}
 
 	fn push(&mut self, token: Tokens) {
+		let (line_number, column, offset) = self.start;
+
 		self.tokens.push((
 			token,
-			Location::new(self.file_name.clone(), self.line_number),
+			Location::new(self.file_name.clone(), line_number).with_span(
+				column,
+				offset,
+				self.offset - offset,
+			),
 		));
 	}
 }
@@ -0,0 +1,269 @@
+// warnings about code that compiles, but most likely doesn't do what was meant
+
+use std::collections::{HashMap, HashSet};
+
+use crate::asdf::{ast, location::Location, Code, Diagnostic, Diagnostics};
+
+// the names `#[allow(...)]` accepts
+pub const LINTS: &[&str] = &[
+	"unused_variables",
+	"unused_assignments",
+	"unreachable_code",
+	"dead_code",
+];
+
+struct Local {
+	name: String,
+	location: Location,
+	is_read: bool,
+	// the address might be written through, so assignments can't be tracked anymore
+	is_borrowed: bool,
+	unused_assignments: Vec<Location>,
+}
+
+struct FunctionData {
+	location: Location,
+	allowed_lints: Vec<String>,
+	// a `pub` function of an imported module, which is used from outside of the sources
+	is_exported: bool,
+	references: HashSet<String>,
+}
+
+pub struct Context {
+	warnings: Diagnostics,
+	functions: HashMap<String, FunctionData>,
+	current_function: Option<String>,
+	allowed_lints: Vec<String>,
+	// locals of the current function by their offset, parameters come before `first_local`
+	locals: HashMap<u32, Local>,
+	first_local: u32,
+	// assignments that haven't been read yet, one map for every nested scope
+	pending: Vec<HashMap<u32, Location>>,
+}
+
+// lints everything together, `dead_code` needs to see every function
+pub fn check(scopes: &[ast::Scope]) -> Diagnostics {
+	let mut ctx = Context::new();
+
+	for i in scopes {
+		ast::Node::lint(i, &mut ctx);
+	}
+
+	ctx.check_dead_code();
+	return ctx.warnings;
+}
+
+impl Context {
+	fn new() -> Self {
+		return Self {
+			warnings: Diagnostics::new(),
+			functions: HashMap::new(),
+			current_function: None,
+			allowed_lints: vec![],
+			locals: HashMap::new(),
+			first_local: 0,
+			pending: vec![],
+		};
+	}
+
+	pub fn start_function(
+		&mut self,
+		name: &str,
+		location: &Location,
+		allowed_lints: &[String],
+		is_exported: bool,
+		first_local: u32,
+	) {
+		self.functions.insert(
+			name.into(),
+			FunctionData {
+				location: location.clone(),
+				allowed_lints: allowed_lints.to_vec(),
+				is_exported,
+				references: HashSet::new(),
+			},
+		);
+
+		self.current_function = Some(name.into());
+		self.allowed_lints = allowed_lints.to_vec();
+		self.locals.clear();
+		self.first_local = first_local;
+	}
+
+	// the assignments still pending are the ones at the end of the function's own scope
+	pub fn end_function(&mut self, pending: HashMap<u32, Location>) {
+		for (offset, location) in pending {
+			if let Some(local) = self.locals.get_mut(&offset) {
+				local.unused_assignments.push(location);
+			}
+		}
+
+		let mut locals: Vec<_> = std::mem::take(&mut self.locals).into_values().collect();
+		locals.sort_by_key(|x| x.location.get_offset());
+
+		for local in locals {
+			if !local.is_read {
+				self.warn(
+					"unused_variables",
+					Code::UnusedVariable,
+					format!("unused variable '{}'", local.name),
+					&local.location,
+				);
+
+				continue;
+			}
+
+			if local.is_borrowed {
+				continue;
+			}
+
+			for location in &local.unused_assignments {
+				self.warn(
+					"unused_assignments",
+					Code::UnusedAssignment,
+					format!("value assigned to '{}' is never read", local.name),
+					location,
+				);
+			}
+		}
+
+		self.current_function = None;
+		self.allowed_lints.clear();
+	}
+
+	// returns the assignments that were never read within the statements
+	pub fn lint_statements(&mut self, nodes: &[ast::BoxedNode]) -> HashMap<u32, Location> {
+		self.pending.push(HashMap::new());
+
+		let mut has_returned = false;
+
+		for node in nodes {
+			if has_returned {
+				self.warn(
+					"unreachable_code",
+					Code::UnreachableCode,
+					format!("unreachable statement"),
+					node.get_location(),
+				);
+
+				// only the first one, the rest follows from it
+				has_returned = false;
+			}
+
+			node.lint(self);
+
+			if let ast::BoxedNode::ReturnStatement(_) = node {
+				has_returned = true;
+			}
+		}
+
+		return self.pending.pop().unwrap_or_default();
+	}
+
+	pub fn read(&mut self, offset: u32) {
+		if let Some(local) = self.locals.get_mut(&offset) {
+			local.is_read = true;
+		}
+
+		for i in &mut self.pending {
+			i.remove(&offset);
+		}
+	}
+
+	pub fn borrow(&mut self, offset: u32) {
+		self.read(offset);
+
+		if let Some(local) = self.locals.get_mut(&offset) {
+			local.is_borrowed = true;
+		}
+	}
+
+	// only an assignment in the same scope overwrites an earlier one, the others might not happen
+	pub fn write(&mut self, offset: u32, name: &str, location: &Location) {
+		if offset < self.first_local || self.current_function.is_none() {
+			return;
+		}
+
+		let local = self.locals.entry(offset).or_insert_with(|| Local {
+			name: name.into(),
+			location: location.clone(),
+			is_read: false,
+			is_borrowed: false,
+			unused_assignments: vec![],
+		});
+
+		if let Some(previous) = self
+			.pending
+			.last_mut()
+			.and_then(|x| x.insert(offset, location.clone()))
+		{
+			local.unused_assignments.push(previous);
+		}
+	}
+
+	pub fn reference(&mut self, function_name: &str) {
+		let Some(current_function) = &self.current_function else {
+			return;
+		};
+
+		if let Some(function) = self.functions.get_mut(current_function) {
+			function.references.insert(function_name.into());
+		}
+	}
+
+	// every function that can't be reached from `main`, trait methods are needed by the trait
+	fn check_dead_code(&mut self) {
+		if !self.functions.contains_key("main") {
+			return;
+		}
+
+		let mut reachable = HashSet::from(["main".to_string()]);
+		let mut queue = vec!["main".to_string()];
+
+		while let Some(name) = queue.pop() {
+			let Some(function) = self.functions.get(&name) else {
+				continue;
+			};
+
+			for i in &function.references {
+				if reachable.insert(i.clone()) {
+					queue.push(i.clone());
+				}
+			}
+		}
+
+		let mut unused: Vec<_> = self
+			.functions
+			.iter()
+			.filter(|(name, _)| !reachable.contains(*name) && !name.starts_with('<'))
+			.filter(|(_, x)| !x.is_exported)
+			.filter(|(_, x)| !x.allowed_lints.iter().any(|x| x == "dead_code"))
+			.map(|(name, x)| (name.clone(), x.location.clone()))
+			.collect();
+
+		unused.sort_by(|(_, x), (_, y)| {
+			(x.get_file_name(), x.get_offset()).cmp(&(y.get_file_name(), y.get_offset()))
+		});
+
+		for (name, location) in unused {
+			self.warn(
+				"dead_code",
+				Code::DeadCode,
+				format!("function '{}' is never used", name),
+				&location,
+			);
+		}
+	}
+
+	fn warn(&mut self, lint: &str, code: Code, message: String, location: &Location) {
+		if self.allowed_lints.iter().any(|x| x == lint) {
+			return;
+		}
+
+		self.warnings.push(
+			Diagnostic::warning(code, message)
+				.with_primary(location.clone())
+				.with_note(format!("silence this with `#[allow({})]`", lint)),
+		);
+	}
+}
@@ -1,9 +1,15 @@
-use crate::asdf::Result;
+use std::fmt::{Display, Formatter};
 
-#[derive(Debug, Default, Clone)]
+use crate::asdf::{Code, Diagnostic, Result};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct Location {
 	file_name: String,
 	line_number: u64,
+	column: u64,
+	// in bytes, from the start of the file
+	offset: usize,
+	length: usize,
 }
 
 impl Location {
@@ -11,15 +17,75 @@ impl Location {
 		return Self {
 			file_name: file_name.into(),
 			line_number,
+			column: 0,
+			offset: 0,
+			length: 0,
 		};
 	}
 
+	pub fn with_span(mut self, column: u64, offset: usize, length: usize) -> Self {
+		self.column = column;
+		self.offset = offset;
+		self.length = length;
+		return self;
+	}
+
+	// from the start of this location to the end of the other one
+	pub fn to(&self, end: &Location) -> Location {
+		if self.file_name != end.file_name || end.offset < self.offset || !self.has_span() {
+			return self.clone();
+		}
+
+		let mut ret = self.clone();
+		ret.length = (end.offset + end.length).max(self.offset + self.length) - self.offset;
+
+		return ret;
+	}
+
+	pub fn get_file_name(&self) -> &str {
+		return &self.file_name;
+	}
+
+	pub fn get_line_number(&self) -> u64 {
+		return self.line_number;
+	}
+
+	pub fn get_column(&self) -> u64 {
+		return self.column;
+	}
+
+	pub fn get_offset(&self) -> usize {
+		return self.offset;
+	}
+
+	pub fn get_length(&self) -> usize {
+		return self.length;
+	}
+
+	// whether this points somewhere inside of the file, and not just to the file itself
+	pub fn has_span(&self) -> bool {
+		return self.column != 0;
+	}
+
+	// syntax errors, coming from the lexer and the parser
 	pub fn error<T, U: Into<String>>(&self, message: U) -> Result<T> {
-		return Err(format!(
-			"{}:{}: {}",
-			self.file_name,
-			self.line_number,
-			message.into()
-		));
+		return Err(Diagnostic::error(Code::Syntax, message)
+			.with_primary(self.clone())
+			.into());
+	}
+}
+
+impl Display for Location {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		// the line isn't known for everything yet
+		if self.line_number == 0 {
+			return write!(f, "{}", self.file_name);
+		}
+
+		if !self.has_span() {
+			return write!(f, "{}:{}", self.file_name, self.line_number);
+		}
+
+		return write!(f, "{}:{}:{}", self.file_name, self.line_number, self.column);
 	}
 }
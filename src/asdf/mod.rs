@@ -2,13 +2,38 @@ pub mod instance;
 
 mod ast;
 mod byte_code;
+mod code_memory;
+mod compiled_module;
+mod diagnostic;
 mod expression_parser;
+mod formatter;
+mod host;
 mod lexer;
+mod lint;
 mod location;
+mod module_loader;
 mod operators;
 mod parser;
 mod parsing_context;
+mod runtime;
+mod stdlib;
 mod tc;
+mod token_cursor;
 mod tokens;
 
-pub type Result<T> = core::result::Result<T, String>;
+pub use code_memory::CodeMemory;
+pub use diagnostic::{Code, Diagnostic, Diagnostics, Severity};
+pub use formatter::format;
+pub use host::{HostArguments, HostFunction, IntoAsdfType};
+pub use location::Location;
+pub use module_loader::{FileSystemProvider, MemoryProvider, SourceProvider};
+pub use runtime::Str;
+pub use tc::Type;
+
+pub type Result<T> = core::result::Result<T, Diagnostics>;
+
+// only the lexer, for the benchmarks, which can't reach the tokens themselves
+#[doc(hidden)]
+pub fn lex(string: &str) -> Result<usize> {
+	return Ok(lexer::Lexer::new(string, String::new()).lex()?.len());
+}
@@ -0,0 +1,216 @@
+use std::{
+	collections::{HashMap, HashSet},
+	path::PathBuf,
+};
+
+use crate::asdf::{
+	ast, expression_parser::ExpressionParser, lexer::Lexer, location::Location, parser::Parser,
+	parsing_context::ParsingContext, tc, token_cursor::TokenCursor, Code, Diagnostic, Result,
+};
+
+// where the source of an imported module comes from
+pub trait SourceProvider {
+	// returns the file name to report errors with, and the source itself
+	fn load(&self, module_name: &str) -> Result<(String, String)>;
+}
+
+// loads `math` from `<root>/math.asdf`
+pub struct FileSystemProvider {
+	root: PathBuf,
+}
+
+impl FileSystemProvider {
+	pub fn new<T: Into<PathBuf>>(root: T) -> Self {
+		return Self { root: root.into() };
+	}
+}
+
+impl SourceProvider for FileSystemProvider {
+	fn load(&self, module_name: &str) -> Result<(String, String)> {
+		let path = self.root.join(format!("{}.asdf", module_name));
+
+		let source = std::fs::read_to_string(&path).map_err(|x| {
+			format!(
+				"cannot load module '{}' from '{}': {}",
+				module_name,
+				path.display(),
+				x
+			)
+		})?;
+
+		return Ok((path.display().to_string(), source));
+	}
+}
+
+#[derive(Default)]
+pub struct MemoryProvider {
+	modules: HashMap<String, String>,
+}
+
+impl MemoryProvider {
+	pub fn new() -> Self {
+		return Self::default();
+	}
+
+	pub fn add_module<T: Into<String>, U: Into<String>>(&mut self, module_name: T, source: U) {
+		self.modules.insert(module_name.into(), source.into());
+	}
+}
+
+impl SourceProvider for MemoryProvider {
+	fn load(&self, module_name: &str) -> Result<(String, String)> {
+		let Some(source) = self.modules.get(module_name) else {
+			return Err(Diagnostic::error(
+				Code::UnknownModule,
+				format!("cannot find module '{}'", module_name),
+			)
+			.into());
+		};
+
+		return Ok((format!("{}.asdf", module_name), source.clone()));
+	}
+}
+
+// a parsed source file, `module_name` is `None` for the ones that were added directly
+pub struct Source {
+	file_name: String,
+	module_name: Option<String>,
+	imports: Vec<String>,
+	scope: ast::Scope,
+	// kept around for showing the lines errors point to
+	text: String,
+}
+
+impl Source {
+	pub fn parse(file_name: &str, module_name: Option<&str>, string: &str) -> Result<Self> {
+		let nodes = Self::parse_nodes(file_name, module_name, string).map_err(|mut x| {
+			x.attach_source(file_name, string);
+			x
+		})?;
+
+		let imports = nodes
+			.iter()
+			.filter_map(|x| match x {
+				ast::BoxedNode::Import(x) => Some(x.get_module_name().to_string()),
+				_ => None,
+			})
+			.collect();
+
+		return Ok(Self {
+			file_name: file_name.into(),
+			module_name: module_name.map(|x| x.into()),
+			imports,
+			scope: ast::Scope::new(nodes, Location::new(file_name, 0)),
+			text: string.into(),
+		});
+	}
+
+	fn parse_nodes(
+		file_name: &str,
+		module_name: Option<&str>,
+		string: &str,
+	) -> Result<Vec<ast::BoxedNode>> {
+		let tokens = Lexer::new(string, file_name.into()).lex()?;
+
+		let mut ctx = ParsingContext::new();
+
+		if let Some(module_name) = module_name {
+			ctx.set_module_name(module_name.into());
+		}
+
+		return Parser::new(tokens).parse(&mut ctx);
+	}
+
+	// a lone expression, wrapped in a function returning it so it's compiled like any other
+	pub fn parse_expression(file_name: &str, function_name: &str, string: &str) -> Result<Self> {
+		let function =
+			Self::parse_function(file_name, function_name, string).map_err(|mut x| {
+				x.attach_source(file_name, string);
+				x
+			})?;
+
+		return Ok(Self {
+			file_name: file_name.into(),
+			module_name: None,
+			imports: vec![],
+			scope: ast::Scope::new(vec![function.into()], Location::new(file_name, 0)),
+			text: string.into(),
+		});
+	}
+
+	fn parse_function(file_name: &str, function_name: &str, string: &str) -> Result<ast::Function> {
+		let mut tokens = TokenCursor::new(Lexer::new(string, file_name.into()).lex()?);
+		let start = tokens.peek_location();
+
+		let mut ctx = ParsingContext::new();
+		ctx.push();
+
+		let node = ExpressionParser::new(&mut tokens).parse(&mut ctx);
+		let (stack_size, variables) = ctx.pop();
+		let node = node?;
+
+		if !tokens.is_empty() {
+			return tokens.peek_location().error(format!(
+				"expected the end of the expression, found {}",
+				tokens.describe_next()
+			));
+		}
+
+		let location = start.to(tokens.get_location());
+
+		let mut function = ast::Function::new(
+			function_name.into(),
+			vec![],
+			tc::Type::I64,
+			vec![],
+			variables,
+			vec![ast::ReturnStatement::new(node, location.clone()).into()],
+			(stack_size + 0x10 - 1) & !(0x10 - 1),
+			location,
+		);
+
+		function.infer_return_type();
+		return Ok(function);
+	}
+
+	pub fn get_file_name(&self) -> &str {
+		return &self.file_name;
+	}
+
+	pub fn get_text(&self) -> &str {
+		return &self.text;
+	}
+
+	pub fn define_module(&self, ctx: &mut tc::Context) {
+		ctx.define_module(self.module_name.clone(), self.imports.clone());
+	}
+
+	pub fn get_scope(&self) -> &ast::Scope {
+		return &self.scope;
+	}
+}
+
+// loads every module the sources import, and the ones those import in turn
+pub fn load_imports(provider: &dyn SourceProvider, sources: &mut Vec<Source>) -> Result<()> {
+	let mut loaded: HashSet<String> = sources
+		.iter()
+		.filter_map(|x| x.module_name.clone())
+		.collect();
+
+	let mut index = 0;
+
+	while index < sources.len() {
+		for module_name in sources[index].imports.clone() {
+			if !loaded.insert(module_name.clone()) {
+				continue;
+			}
+
+			let (file_name, string) = provider.load(&module_name)?;
+			sources.push(Source::parse(&file_name, Some(&module_name), &string)?);
+		}
+
+		index += 1;
+	}
+
+	return Ok(());
+}
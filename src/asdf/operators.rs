@@ -8,6 +8,10 @@ pub enum Operators {
 	Modulo,
 	Equals,
 	NotEquals,
+	LessThan,
+	GreaterThan,
+	LessThanOrEqual,
+	GreaterThanOrEqual,
 	LogicalAnd,
 	LogicalOr,
 }
@@ -20,9 +24,13 @@ impl Operators {
 			Self::Subtraction => 3,
 			Self::Multiplication => 4,
 			Self::Division => 4,
-			Self::Modulo => 3,
+			Self::Modulo => 4,
 			Self::Equals => 2,
 			Self::NotEquals => 2,
+			Self::LessThan => 2,
+			Self::GreaterThan => 2,
+			Self::LessThanOrEqual => 2,
+			Self::GreaterThanOrEqual => 2,
 			Self::LogicalAnd => 1,
 			Self::LogicalOr => 1,
 		};
@@ -1,58 +1,277 @@
-use crate::asdf::Result;
-use std::collections::VecDeque;
+use crate::asdf::{Diagnostics, Result};
 
 use super::{
-	ast, expression_parser::ExpressionParser, location::Location, operators::Operators,
-	parsing_context::ParsingContext, tc, tokens::Tokens,
+	ast, expression_parser::ExpressionParser, lint, location::Location, operators::Operators,
+	parsing_context::ParsingContext, tc, token_cursor::TokenCursor, tokens::Tokens,
 };
 
+// name, type parameters, parameters and the return type
+type FunctionSignature = (String, Vec<String>, Vec<(String, tc::Type)>, tc::Type);
+
 pub struct Parser {
-	tokens: VecDeque<(Tokens, Location)>,
+	tokens: TokenCursor,
+	// the statements of the block that is being parsed
 	nodes: Vec<ast::BoxedNode>,
-	location: Location,
+	self_type: Option<tc::Type>,
+	// the lints allowed by the attributes in front of the next function
+	allowed_lints: Vec<String>,
+	// the doc comments in front of the next function
+	doc: Vec<String>,
 }
 
 impl Parser {
-	pub fn new(tokens: VecDeque<(Tokens, Location)>) -> Self {
+	pub fn new(tokens: Vec<(Tokens, Location)>) -> Self {
 		return Self {
-			tokens,
+			tokens: TokenCursor::new(tokens),
 			nodes: vec![],
-			location: Location::default(),
+			self_type: None,
+			allowed_lints: vec![],
+			doc: vec![],
 		};
 	}
 
 	pub fn parse(mut self, ctx: &mut ParsingContext) -> Result<Vec<ast::BoxedNode>> {
-		while !self.tokens.is_empty() {
-			if self.return_statement(ctx)? {
-				continue;
-			}
+		return self.parse_statements(ctx, false);
+	}
 
-			if self.if_statement(ctx)? {
-				continue;
+	// a statement that can't be parsed is skipped, so every syntax error is reported at once
+	fn parse_statements(
+		&mut self,
+		ctx: &mut ParsingContext,
+		in_block: bool,
+	) -> Result<Vec<ast::BoxedNode>> {
+		let outer = std::mem::take(&mut self.nodes);
+		let mut diagnostics = Diagnostics::new();
+
+		while let Some(token) = self.tokens.peek() {
+			if in_block && token == Tokens::CurlyClose {
+				break;
 			}
 
-			if self.while_statement(ctx)? {
+			let position = self.tokens.get_position();
+
+			let Err(x) = self.statement(ctx) else {
 				continue;
-			}
+			};
+
+			diagnostics.extend(x);
 
-			if self.variable_declaration(ctx)? {
+			// a '}' without a block to close
+			if position == self.tokens.get_position() && token == Tokens::CurlyClose {
+				self.tokens.pop();
 				continue;
 			}
 
-			if self.function_definition(ctx)? {
-				continue;
+			// errors inside of a block leave the statement read all the way to its end
+			if position == self.tokens.get_position()
+				|| !matches!(
+					self.tokens.get_last_token(),
+					Some(Tokens::SemiColon | Tokens::CurlyClose)
+				) {
+				self.synchronize();
 			}
+		}
 
-			if self.expression(ctx)? {
-				continue;
+		// there's no function after them in this block
+		self.allowed_lints.clear();
+		self.doc.clear();
+
+		let nodes = std::mem::replace(&mut self.nodes, outer);
+
+		if diagnostics.has_errors() {
+			return Err(diagnostics);
+		}
+
+		return Ok(nodes);
+	}
+
+	// `{ ... }`, the statements and where the braces are
+	fn block(
+		&mut self,
+		ctx: &mut ParsingContext,
+		self_type: Option<tc::Type>,
+	) -> Result<(Vec<ast::BoxedNode>, Location)> {
+		self.tokens.enter()?;
+		let ret = self.nested_block(ctx, self_type);
+		self.tokens.leave();
+
+		return ret;
+	}
+
+	fn nested_block(
+		&mut self,
+		ctx: &mut ParsingContext,
+		self_type: Option<tc::Type>,
+	) -> Result<(Vec<ast::BoxedNode>, Location)> {
+		self.tokens.pop_checked(Tokens::CurlyOpen)?;
+		let start = self.tokens.get_location().clone();
+
+		let outer_self_type = std::mem::replace(&mut self.self_type, self_type);
+
+		let nodes = self.parse_statements(ctx, true);
+		let close = self.tokens.pop_checked(Tokens::CurlyClose);
+
+		self.self_type = outer_self_type;
+
+		let nodes = match (nodes, close) {
+			(Ok(x), Ok(())) => x,
+			(Err(mut x), Err(y)) => {
+				x.extend(y);
+				return Err(x);
 			}
+			(Err(x), _) | (_, Err(x)) => return Err(x),
+		};
+
+		return Ok((nodes, start.to(self.tokens.get_location())));
+	}
+
+	fn statement(&mut self, ctx: &mut ParsingContext) -> Result<()> {
+		if self.attribute()? {
+			return Ok(());
+		}
+
+		if self.doc_comment() {
+			return Ok(());
+		}
+
+		if !self.allowed_lints.is_empty()
+			&& !matches!(self.peek(), Some(Tokens::Fn) | Some(Tokens::Pub))
+		{
+			self.allowed_lints.clear();
 
 			return self
-				.location
-				.error(format!("cannot parse {:?}", self.tokens));
+				.peek_location()
+				.error(format!("attributes can only be put on functions"));
+		}
+
+		// like any other comment, unless it documents a function
+		if !matches!(self.peek(), Some(Tokens::Fn) | Some(Tokens::Pub)) {
+			self.doc.clear();
+		}
+
+		// a function is type checked in one go, nothing else can be checked in the middle of it
+		if ctx.is_in_function() {
+			if let Some(
+				x @ (Tokens::Fn | Tokens::Pub | Tokens::Impl | Tokens::Trait | Tokens::Import),
+			) = self.peek()
+			{
+				return self
+					.peek_location()
+					.error(format!("{} is only allowed outside of functions", x));
+			}
+		}
+
+		if self.return_statement(ctx)? {
+			return Ok(());
+		}
+
+		if self.if_statement(ctx)? {
+			return Ok(());
+		}
+
+		if self.while_statement(ctx)? {
+			return Ok(());
+		}
+
+		if self.variable_declaration(ctx)? {
+			return Ok(());
+		}
+
+		if self.import_statement()? {
+			return Ok(());
+		}
+
+		if self.trait_definition()? {
+			return Ok(());
+		}
+
+		if self.implementation(ctx)? {
+			return Ok(());
 		}
 
-		return Ok(self.nodes);
+		if self.function_definition(ctx)? {
+			return Ok(());
+		}
+
+		if self.expression(ctx)? {
+			return Ok(());
+		}
+
+		let found = self.pop();
+		return self.unexpected("a statement", found);
+	}
+
+	// skips to the end of the current statement, or the block it's in
+	fn synchronize(&mut self) {
+		let mut depth = 0;
+
+		while let Some(token) = self.tokens.peek() {
+			match token {
+				// closes the block the statement is in, which is left for it
+				Tokens::CurlyClose if depth == 0 => return,
+				Tokens::CurlyClose => depth -= 1,
+				Tokens::CurlyOpen => depth += 1,
+				_ => {}
+			}
+
+			self.tokens.pop();
+
+			if depth == 0 && matches!(token, Tokens::SemiColon | Tokens::CurlyClose) {
+				return;
+			}
+		}
+	}
+
+	// `#[allow(dead_code, unused_variables)]`
+	fn attribute(&mut self) -> Result<bool> {
+		let Some(Tokens::Hash) = self.peek() else {
+			return Ok(false);
+		};
+
+		self.pop();
+		self.pop_checked(Tokens::BracketOpen)?;
+
+		match self.pop() {
+			Some((Tokens::Identifier(x), _)) if x == "allow" => {}
+			x => return self.unexpected("an attribute", x),
+		}
+
+		self.pop_checked(Tokens::ParenOpen)?;
+
+		loop {
+			match self.pop() {
+				Some((Tokens::Identifier(x), _)) if lint::LINTS.contains(&x.as_str()) => {
+					self.allowed_lints.push(x)
+				}
+				Some((Tokens::Identifier(x), _)) => {
+					return self
+						.tokens
+						.get_location()
+						.error(format!("unknown lint '{}'", x))
+				}
+				x => return self.unexpected("a lint name", x),
+			}
+
+			match self.pop() {
+				Some((Tokens::Comma, _)) => {}
+				Some((Tokens::ParenClose, _)) => break,
+				x => return self.unexpected("',' or ')'", x),
+			}
+		}
+
+		self.pop_checked(Tokens::BracketClose)?;
+		return Ok(true);
+	}
+
+	fn doc_comment(&mut self) -> bool {
+		let Some(Tokens::DocComment(line)) = self.peek() else {
+			return false;
+		};
+
+		self.pop();
+		self.doc.push(line);
+
+		return true;
 	}
 
 	fn return_statement(&mut self, ctx: &mut ParsingContext) -> Result<bool> {
@@ -60,13 +279,14 @@ impl Parser {
 			return Ok(false);
 		};
 
+		let start = self.peek_location();
 		self.pop();
 
-		let tokens = self.pop_until(Tokens::SemiColon)?;
-		let node = ExpressionParser::new(tokens).parse(ctx)?;
+		let node = ExpressionParser::new(&mut self.tokens).parse(ctx)?;
+		self.tokens.pop_checked(Tokens::SemiColon)?;
 
-		self.pop();
-		self.push(ast::ReturnStatement::new(node).into());
+		let location = start.to(self.tokens.get_location());
+		self.push(ast::ReturnStatement::new(node, location).into());
 
 		return Ok(true);
 	}
@@ -76,17 +296,15 @@ impl Parser {
 			return Ok(false);
 		};
 
+		let start = self.peek_location();
 		self.pop();
 
-		let tokens = self.pop_until(Tokens::CurlyOpen)?;
-		let node = ExpressionParser::new(tokens).parse(ctx)?;
-
-		let scope_tokens = self.pop_scope(Tokens::CurlyOpen, Tokens::CurlyClose)?;
+		let node = ExpressionParser::new(&mut self.tokens).parse(ctx)?;
+		let (nodes, scope_location) = self.block(ctx, None)?;
+		let scope = ast::Scope::new(nodes, scope_location);
 
-		let nodes = Self::new(scope_tokens).parse(ctx)?;
-		let scope = ast::Scope::new(nodes);
-
-		self.push(ast::IfStatement::new(node, scope).into());
+		let location = start.to(self.tokens.get_location());
+		self.push(ast::IfStatement::new(node, scope, location).into());
 		return Ok(true);
 	}
 
@@ -95,17 +313,15 @@ impl Parser {
 			return Ok(false);
 		};
 
+		let start = self.peek_location();
 		self.pop();
 
-		let tokens = self.pop_until(Tokens::CurlyOpen)?;
-		let condition = ExpressionParser::new(tokens).parse(ctx)?;
-
-		let scope_tokens = self.pop_scope(Tokens::CurlyOpen, Tokens::CurlyClose)?;
-
-		let nodes = Self::new(scope_tokens).parse(ctx)?;
-		let scope = ast::Scope::new(nodes);
+		let condition = ExpressionParser::new(&mut self.tokens).parse(ctx)?;
+		let (nodes, scope_location) = self.block(ctx, None)?;
+		let scope = ast::Scope::new(nodes, scope_location);
 
-		self.push(ast::WhileStatement::new(condition, scope).into());
+		let location = start.to(self.tokens.get_location());
+		self.push(ast::WhileStatement::new(condition, scope, location).into());
 		return Ok(true);
 	}
 
@@ -116,35 +332,40 @@ impl Parser {
 			_ => return Ok(false),
 		};
 
+		let start = self.peek_location();
 		self.pop();
 
-		let name = match self.pop() {
-			Some((Tokens::Identifier(name), _)) => name,
-			x => {
-				return self
-					.location
-					.error(format!("expected Some(Identifier), got {:?}", x))
-			}
+		if !ctx.is_in_function() {
+			return start.error(format!(
+				"variables can only be declared inside of functions"
+			));
+		}
+
+		let (name, name_location) = match self.pop() {
+			Some((Tokens::Identifier(name), location)) => (name, location),
+			x => return self.unexpected("a variable name", x),
 		};
 
-		self.pop_checked(Tokens::Equals)?;
+		if let Some(Tokens::SemiColon) = self.peek() {
+			self.pop();
+			ctx.push_local(name);
 
-		let tokens = self.pop_until(Tokens::SemiColon)?;
-		let value = ExpressionParser::new(tokens).parse(ctx)?;
+			return Ok(true);
+		}
 
-		self.pop();
+		self.pop_checked(Tokens::Equals)?;
 
-		// TODO: this is very temporary, type checking shouldn't be needed for getting the type
-		let value_type = value.type_check(ctx.get_type_checking_context())?;
+		let value = ExpressionParser::new(&mut self.tokens).parse(ctx)?;
+		self.pop_checked(Tokens::SemiColon)?;
 
-		let size = value_type.get_size() as u32;
-		let offset = ctx.push_variable(value_type.clone(), name, size);
+		let (value_type, offset) = ctx.push_local(name.clone());
 
 		self.push(
 			ast::BinaryOperation::new(
 				Operators::Assignment,
-				ast::Stack::new(value_type, offset, size).into(),
+				ast::Stack::new(name, value_type, offset, 8, name_location).into(),
 				value,
+				start.to(self.tokens.get_location()),
 			)
 			.into(),
 		);
@@ -152,29 +373,118 @@ impl Parser {
 		return Ok(true);
 	}
 
-	fn function_definition(&mut self, ctx: &mut ParsingContext) -> Result<bool> {
-		let Some(Tokens::Fn) = self.peek() else {
+	fn import_statement(&mut self) -> Result<bool> {
+		let Some(Tokens::Import) = self.peek() else {
 			return Ok(false);
 		};
 
+		let start = self.peek_location();
 		self.pop();
 
-		let function_name = match self.pop() {
+		let module_name = match self.pop() {
 			Some((Tokens::Identifier(name), _)) => name,
-			x => {
+			x => return self.unexpected("a module name", x),
+		};
+
+		self.pop_checked(Tokens::SemiColon)?;
+
+		self.push(ast::Import::new(module_name, start.to(self.tokens.get_location())).into());
+		return Ok(true);
+	}
+
+	fn trait_definition(&mut self) -> Result<bool> {
+		let Some(Tokens::Trait) = self.peek() else {
+			return Ok(false);
+		};
+
+		let start = self.peek_location();
+		self.pop();
+
+		let trait_name = match self.pop() {
+			Some((Tokens::Identifier(name), _)) => name,
+			x => return self.unexpected("a trait name", x),
+		};
+
+		self.pop_checked(Tokens::CurlyOpen)?;
+
+		let outer_self_type = self.self_type.replace(tc::Type::Generic("Self".into()));
+		let methods = self.trait_methods();
+		self.self_type = outer_self_type;
+
+		let methods = methods?;
+		let location = start.to(self.tokens.get_location());
+
+		self.push(ast::TraitDefinition::new(trait_name, methods, location).into());
+		return Ok(true);
+	}
+
+	fn implementation(&mut self, ctx: &mut ParsingContext) -> Result<bool> {
+		let Some(Tokens::Impl) = self.peek() else {
+			return Ok(false);
+		};
+
+		let start = self.peek_location();
+		self.pop();
+
+		// `impl Trait for Type` or just `impl Type`
+		let trait_name = match (self.peek(), self.peek_nth(1)) {
+			(Some(Tokens::Identifier(name)), Some(Tokens::For)) => {
+				self.pop();
+				self.pop();
+
+				Some(name)
+			}
+			_ => None,
+		};
+
+		let self_type = self.parse_type(&[])?;
+		let (nodes, _) = self.block(ctx, Some(self_type.clone()))?;
+		let location = start.to(self.tokens.get_location());
+
+		let mut functions = vec![];
+
+		for node in nodes {
+			let ast::BoxedNode::Function(function) = node else {
 				return self
-					.location
-					.error(format!("expected Some(Identifier), got {:?}", x))
+					.tokens
+					.get_location()
+					.error(format!("expected only functions in an implementation"));
+			};
+
+			functions.push(function);
+		}
+
+		self.push(ast::Implementation::new(trait_name, self_type, functions, location).into());
+		return Ok(true);
+	}
+
+	fn function_definition(&mut self, ctx: &mut ParsingContext) -> Result<bool> {
+		let start = self.peek_location();
+
+		let is_public = match (self.peek(), self.peek_nth(1)) {
+			(Some(Tokens::Pub), Some(Tokens::Fn)) => {
+				self.pop();
+				true
 			}
+			(Some(Tokens::Fn), _) => false,
+			_ => return Ok(false),
 		};
 
-		self.pop_checked(Tokens::ParenOpen)?;
+		let allowed_lints = std::mem::take(&mut self.allowed_lints);
+		let doc = std::mem::take(&mut self.doc);
 
-		let params = self.parse_function_params()?;
-		let types = params.iter().map(|(_, typ)| typ.clone()).collect();
+		let (mut function_name, type_parameters, params, return_type) =
+			self.parse_function_signature()?;
 
-		self.pop_checked(Tokens::ParenClose)?;
-		let tokens = self.pop_scope(Tokens::CurlyOpen, Tokens::CurlyClose)?;
+		// only the signature, the body would make for a long underline
+		let location = start.to(self.tokens.get_location());
+
+		// methods are named after their type instead
+		if let (Some(module_name), None) = (ctx.get_module_name(), &self.self_type) {
+			function_name = format!("{}::{}", module_name, function_name);
+		}
+
+		let types = params.iter().map(|(_, typ)| typ.clone()).collect();
 
 		ctx.push();
 
@@ -183,39 +493,57 @@ impl Parser {
 			ctx.push_variable(typ, name, size);
 		}
 
-		let nodes = Self::new(tokens).parse(ctx)?;
-		let stack_size = ctx.pop();
-
-		self.push(
-			ast::Function::new(
-				function_name,
-				tc::Type::I64,
-				types,
-				nodes,
-				(stack_size + 0x10 - 1) & !(0x10 - 1),
-			)
-			.into(),
+		// the variables have to be popped even if the body doesn't parse
+		let nodes = self.block(ctx, None);
+		let (stack_size, variables) = ctx.pop();
+		let (nodes, _) = nodes?;
+
+		let mut function = ast::Function::new(
+			function_name,
+			type_parameters,
+			return_type,
+			types,
+			variables,
+			nodes,
+			(stack_size + 0x10 - 1) & !(0x10 - 1),
+			location,
 		);
 
+		function.set_allowed_lints(allowed_lints);
+
+		if !doc.is_empty() {
+			function.set_doc(doc.join("\n"));
+		}
+
+		if let Some(module_name) = ctx.get_module_name() {
+			// methods can be called from anywhere the type can be used
+			function.set_module(module_name.into(), is_public || self.self_type.is_some());
+		}
+
+		self.push(function.into());
+
 		return Ok(true);
 	}
 
 	fn expression(&mut self, ctx: &mut ParsingContext) -> Result<bool> {
-		let tokens = self.pop_until(Tokens::SemiColon)?;
-		let expression = ExpressionParser::new(tokens).parse(ctx)?;
+		let expression = ExpressionParser::new(&mut self.tokens).parse(ctx)?;
+		self.pop_checked(Tokens::SemiColon)?;
 
-		self.pop();
 		self.push(expression);
 
 		return Ok(true);
 	}
 
 	fn peek(&self) -> Option<Tokens> {
-		let Some((ret, _)) = self.tokens.front() else {
-			return None;
-		};
+		return self.tokens.peek();
+	}
+
+	fn peek_nth(&self, index: usize) -> Option<Tokens> {
+		return self.tokens.peek_nth(index);
+	}
 
-		return Some(ret.clone());
+	fn peek_location(&self) -> Location {
+		return self.tokens.peek_location();
 	}
 
 	fn push(&mut self, node: ast::BoxedNode) {
@@ -223,82 +551,117 @@ impl Parser {
 	}
 
 	fn pop(&mut self) -> Option<(Tokens, Location)> {
-		let Some((tok, loc)) = self.tokens.pop_front() else {
-			return None;
-		};
-
-		self.location = loc.clone();
-		return Some((tok, loc));
+		return self.tokens.pop();
 	}
 
 	fn pop_checked(&mut self, expected: Tokens) -> Result<()> {
-		let Some((tok, _)) = self.pop() else {
-			return self
-				.location
-				.error(format!("expected {:?}, got nothing", expected));
-		};
-
-		if tok != expected {
-			return self
-				.location
-				.error(format!("expected {:?}, got {:?}", expected, tok));
-		}
+		return self.tokens.pop_checked(expected);
+	}
 
-		return Ok(());
+	fn unexpected<T>(&mut self, expected: &str, found: Option<(Tokens, Location)>) -> Result<T> {
+		return self.tokens.unexpected(expected, found);
 	}
 
-	fn pop_while<T: FnMut(&Self, Option<&(Tokens, Location)>) -> Result<bool>>(
-		&mut self,
-		mut predicate: T,
-	) -> Result<VecDeque<(Tokens, Location)>> {
-		let mut ret = VecDeque::new();
+	// the signatures of a trait, up to and including the '}'
+	fn trait_methods(&mut self) -> Result<Vec<(String, tc::Type)>> {
+		let mut ret = vec![];
 
-		while predicate(self, self.tokens.front())? {
-			ret.push_back(self.pop().unwrap());
+		while !matches!(self.peek(), Some(Tokens::CurlyClose) | None) {
+			// nothing keeps the documentation of trait methods
+			if let Some(Tokens::DocComment(_)) = self.peek() {
+				self.pop();
+				continue;
+			}
+
+			let (name, _, params, return_type) = self.parse_function_signature()?;
+			self.pop_checked(Tokens::SemiColon)?;
+
+			let types = params.into_iter().map(|(_, typ)| typ).collect();
+			ret.push((name, tc::Type::Function(Box::new(return_type), types)));
 		}
 
+		self.pop_checked(Tokens::CurlyClose)?;
 		return Ok(ret);
 	}
 
-	fn pop_until(&mut self, token_type: Tokens) -> Result<VecDeque<(Tokens, Location)>> {
-		let ret = self.pop_while(|this, x| {
-			let Some((typ, _)) = x else {
-				return this
-					.location
-					.error(format!("expected {:?}, got nothing", token_type));
-			};
+	fn parse_function_signature(&mut self) -> Result<FunctionSignature> {
+		self.pop_checked(Tokens::Fn)?;
+
+		let function_name = match self.pop() {
+			Some((Tokens::Identifier(name), _)) => name,
+			x => return self.unexpected("a function name", x),
+		};
 
-			return Ok(*typ != token_type);
-		})?;
+		let type_parameters = self.parse_type_parameters()?;
+		self.pop_checked(Tokens::ParenOpen)?;
 
-		return Ok(ret);
+		let params = self.parse_function_params(&type_parameters)?;
+
+		self.pop_checked(Tokens::ParenClose)?;
+		let return_type = self.parse_return_type(&type_parameters)?;
+
+		return Ok((function_name, type_parameters, params, return_type));
 	}
 
-	fn pop_scope(&mut self, open: Tokens, close: Tokens) -> Result<VecDeque<(Tokens, Location)>> {
-		let mut diff = 1;
-		self.pop_checked(open.clone())?;
+	fn parse_type_parameters(&mut self) -> Result<Vec<String>> {
+		let mut ret = vec![];
 
-		let ret = self.pop_while(|this, x| {
-			let Some((typ, _)) = x else {
-				return this
-					.location
-					.error(format!("expected {:?}, got nothing", close));
-			};
+		let Some(Tokens::LessThan) = self.peek() else {
+			return Ok(ret);
+		};
+
+		self.pop();
+
+		loop {
+			match self.pop() {
+				Some((Tokens::Identifier(x), _)) => ret.push(x),
+				x => return self.unexpected("a type parameter", x),
+			}
 
-			if *typ == open {
-				diff += 1;
-			} else if *typ == close {
-				diff -= 1;
+			match self.pop() {
+				Some((Tokens::Comma, _)) => {}
+				Some((Tokens::GreaterThan, _)) => break,
+				x => return self.unexpected("',' or '>'", x),
 			}
+		}
 
-			return Ok(diff > 0);
-		})?;
+		return Ok(ret);
+	}
+
+	fn parse_return_type(&mut self, type_parameters: &[String]) -> Result<tc::Type> {
+		let (Some(Tokens::Minus), Some(Tokens::GreaterThan)) = (self.peek(), self.peek_nth(1))
+		else {
+			return Ok(tc::Type::I64);
+		};
 
 		self.pop();
-		return Ok(ret);
+		self.pop();
+
+		return self.parse_type(type_parameters);
 	}
 
-	fn parse_function_params(&mut self) -> Result<Vec<(String, tc::Type)>> {
+	fn parse_type(&mut self, type_parameters: &[String]) -> Result<tc::Type> {
+		return match self.pop() {
+			Some((Tokens::I64, _)) => Ok(tc::Type::I64),
+			Some((Tokens::Char, _)) => Ok(tc::Type::Char),
+			Some((Tokens::Str, _)) => Ok(tc::Type::Str),
+			Some((Tokens::Star, _)) => Ok(tc::Type::Pointer(Box::new(
+				self.parse_type(type_parameters)?,
+			))),
+			Some((Tokens::Identifier(x), _)) if type_parameters.contains(&x) => {
+				Ok(tc::Type::Generic(x))
+			}
+			Some((Tokens::Identifier(x), _)) if x == "Self" && self.self_type.is_some() => {
+				Ok(self.self_type.clone().unwrap())
+			}
+			x => self.unexpected("a type", x),
+		};
+	}
+
+	fn parse_function_params(
+		&mut self,
+		type_parameters: &[String],
+	) -> Result<Vec<(String, tc::Type)>> {
 		let mut ret = vec![];
 
 		// TODO: clean this up
@@ -309,29 +672,12 @@ impl Parser {
 
 			let ident = match self.pop() {
 				Some((Tokens::Identifier(x), _)) => x,
-				Some((tok, _)) => {
-					return self
-						.location
-						.error(format!("expected Identifier, got {:?}", tok))
-				}
-				_ => {
-					return self
-						.location
-						.error(format!("expected Identifier, got nothing"))
-				}
+				x => return self.unexpected("a parameter name", x),
 			};
 
 			self.pop_checked(Tokens::Colon)?;
 
-			let typ = match self.pop() {
-				Some((Tokens::I64, _)) => tc::Type::I64,
-				Some((tok, _)) => {
-					return self
-						.location
-						.error(format!("expected a type, got {:?}", tok))
-				}
-				_ => return self.location.error(format!("expected a type, got nothing")),
-			};
+			let typ = self.parse_type(type_parameters)?;
 
 			match self.peek() {
 				Some(Tokens::ParenClose) => {}
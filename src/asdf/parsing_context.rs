@@ -4,24 +4,32 @@ use crate::asdf::tc;
 
 struct FunctionData {
 	variables: HashMap<String, (tc::Type, u32, u32)>,
+	locals: Vec<String>,
 	current_offset: u32,
 }
 
-pub struct ParsingContext<'a> {
-	extern_functions: HashMap<String, (u64, tc::Type, Vec<tc::Type>)>,
-	type_checking_context: &'a mut tc::Context,
+pub struct ParsingContext {
 	functions: Vec<FunctionData>,
+	module_name: Option<String>,
 }
 
-impl<'a> ParsingContext<'a> {
-	pub fn new(type_checking_context: &'a mut tc::Context) -> Self {
+impl ParsingContext {
+	pub fn new() -> Self {
 		return Self {
-			extern_functions: HashMap::new(),
-			type_checking_context,
 			functions: Vec::new(),
+			module_name: None,
 		};
 	}
 
+	// the functions of an imported module are all prefixed with its name
+	pub fn set_module_name(&mut self, module_name: String) {
+		self.module_name = Some(module_name);
+	}
+
+	pub fn get_module_name(&self) -> Option<&str> {
+		return self.module_name.as_deref();
+	}
+
 	// TODO: error handling
 	pub fn push_variable(&mut self, value_type: tc::Type, name: String, size: u32) -> u32 {
 		let func = self.get_fn_mut();
@@ -34,51 +42,41 @@ impl<'a> ParsingContext<'a> {
 		return current_offset;
 	}
 
-	pub fn find_variable(&self, name: &str) -> Option<(tc::Type, u32, u32)> {
-		let Some(ret) = self.get_fn().variables.get(name) else {
-			return None;
-		};
+	// the type of a local is left to be inferred after parsing
+	pub fn push_local(&mut self, name: String) -> (tc::Type, u32) {
+		let func = self.get_fn_mut();
 
-		return Some(ret.clone());
-	}
+		let value_type = tc::Type::Variable(func.locals.len());
+		func.locals.push(name.clone());
 
-	// TODO: error handling
-	pub fn extern_function<T: Into<String>>(
-		&mut self,
-		name: T,
-		function: u64,
-		return_type: tc::Type,
-		argument_types: Vec<tc::Type>,
-	) {
-		self.extern_functions
-			.insert(name.into(), (function, return_type, argument_types));
+		// every value lives in a 64-bit register, so every local gets a full slot
+		let offset = self.push_variable(value_type.clone(), name, 8);
+		return (value_type, offset);
 	}
 
-	pub fn find_extern_function(&self, name: &str) -> Option<(u64, tc::Type, Vec<tc::Type>)> {
-		let Some(ret) = self.extern_functions.get(name) else {
+	pub fn find_variable(&self, name: &str) -> Option<(tc::Type, u32, u32)> {
+		let Some(ret) = self.functions.last()?.variables.get(name) else {
 			return None;
 		};
 
 		return Some(ret.clone());
 	}
 
-	pub fn get_type_checking_context(&mut self) -> &mut tc::Context {
-		return self.type_checking_context;
+	pub fn is_in_function(&self) -> bool {
+		return !self.functions.is_empty();
 	}
 
 	pub fn push(&mut self) {
 		self.functions.push(FunctionData {
 			variables: HashMap::new(),
+			locals: Vec::new(),
 			current_offset: u32::default(),
 		});
 	}
 
-	pub fn pop(&mut self) -> u32 {
-		return self.functions.pop().unwrap().current_offset;
-	}
-
-	fn get_fn(&self) -> &FunctionData {
-		return self.functions.last().unwrap();
+	pub fn pop(&mut self) -> (u32, Vec<String>) {
+		let func = self.functions.pop().unwrap();
+		return (func.current_offset, func.locals);
 	}
 
 	fn get_fn_mut(&mut self) -> &mut FunctionData {
@@ -0,0 +1,164 @@
+// helpers the generated code calls into
+
+use std::{
+	cell::{Cell, RefCell},
+	collections::HashMap,
+	fs::File,
+	os::fd::AsRawFd,
+};
+
+use crate::asdf::{tc, Code, Diagnostic, Result};
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct Str {
+	ptr: *const u8,
+	len: u64,
+}
+
+impl Str {
+	pub fn as_bytes(&self) -> &[u8] {
+		return unsafe { std::slice::from_raw_parts(self.ptr, self.len as usize) };
+	}
+
+	// owned by the runtime of the instance whose code is running
+	pub fn new(value: Vec<u8>) -> *const Str {
+		let value = OwnedStr::new(value);
+		let ret = value.get_address() as *const Str;
+
+		with_runtime(|x| x.strings.push(value));
+		return ret;
+	}
+}
+
+// a str along with the bytes it points to, string literals live for as long as the code
+// referencing them and the strings created at runtime for as long as their instance
+#[derive(Debug)]
+pub struct OwnedStr {
+	header: Box<Str>,
+	_value: Box<[u8]>,
+}
+
+impl OwnedStr {
+	pub fn new<T: Into<Box<[u8]>>>(value: T) -> Self {
+		let value: Box<[u8]> = value.into();
+
+		return Self {
+			header: Box::new(Str {
+				ptr: value.as_ptr(),
+				len: value.len() as u64,
+			}),
+			_value: value,
+		};
+	}
+
+	pub fn get_address(&self) -> u64 {
+		return self.header.as_ref() as *const Str as u64;
+	}
+}
+
+// what the code of an instance creates while it runs, it's freed along with the instance
+#[derive(Debug, Default)]
+pub struct Runtime {
+	strings: Vec<OwnedStr>,
+	// the files `open` handed out, by their descriptor
+	files: HashMap<i64, File>,
+	// the first error raised, the code keeps running until it returns
+	error: Option<String>,
+}
+
+thread_local! {
+	static CURRENT: Cell<*const RefCell<Runtime>> = const { Cell::new(std::ptr::null()) };
+	// for the runtime functions called by the host directly, outside of any instance
+	static DETACHED: RefCell<Runtime> = RefCell::new(Runtime::default());
+}
+
+impl Runtime {
+	// calls into generated code with `runtime` as the current one, an error raised while it ran
+	// is returned instead of the result
+	pub fn run<T>(runtime: &RefCell<Runtime>, function: impl FnOnce() -> T) -> Result<T> {
+		let previous = CURRENT.replace(runtime);
+		let ret = function();
+		CURRENT.set(previous);
+
+		if let Some(x) = runtime.borrow_mut().error.take() {
+			return Err(Diagnostic::error(Code::Runtime, x).into());
+		}
+
+		return Ok(ret);
+	}
+
+	pub fn add_file(&mut self, file: File) -> i64 {
+		let ret = file.as_raw_fd() as i64;
+		self.files.insert(ret, file);
+
+		return ret;
+	}
+
+	// only the files that were opened through the runtime, never any other descriptor
+	pub fn get_file(&mut self, fd: i64) -> Option<&mut File> {
+		return self.files.get_mut(&fd);
+	}
+
+	pub fn remove_file(&mut self, fd: i64) -> Option<File> {
+		return self.files.remove(&fd);
+	}
+}
+
+pub fn with_runtime<T>(function: impl FnOnce(&mut Runtime) -> T) -> T {
+	let current = CURRENT.get();
+
+	if current.is_null() {
+		return DETACHED.with(|x| function(&mut x.borrow_mut()));
+	}
+
+	// the pointer is only set while `Runtime::run` borrows the runtime
+	return function(&mut unsafe { &*current }.borrow_mut());
+}
+
+// there is no way to unwind through the generated code, so the error is reported once it returns
+pub fn raise(message: String) {
+	with_runtime(|x| {
+		x.error.get_or_insert(message);
+	});
+}
+
+pub extern "C" fn str_len(x: *const Str) -> i64 {
+	return unsafe { (*x).len as i64 };
+}
+
+pub extern "C" fn str_concat(lhs: *const Str, rhs: *const Str) -> *const Str {
+	let (lhs, rhs) = unsafe { ((*lhs).as_bytes(), (*rhs).as_bytes()) };
+	return Str::new([lhs, rhs].concat());
+}
+
+pub extern "C" fn str_equals(lhs: *const Str, rhs: *const Str) -> i64 {
+	let (lhs, rhs) = unsafe { ((*lhs).as_bytes(), (*rhs).as_bytes()) };
+	return (lhs == rhs) as i64;
+}
+
+pub extern "C" fn str_index(x: *const Str, index: i64) -> i64 {
+	let x = unsafe { (*x).as_bytes() };
+
+	let Some(ret) = usize::try_from(index).ok().and_then(|i| x.get(i)) else {
+		raise(format!(
+			"index {} is out of bounds for a str of length {}",
+			index,
+			x.len()
+		));
+
+		return 0;
+	};
+
+	return *ret as i64;
+}
+
+pub fn define(ctx: &mut tc::Context) {
+	ctx.extern_function(
+		"str::len",
+		str_len as u64,
+		tc::Type::I64,
+		vec![tc::Type::Str],
+	);
+	ctx.define_method(tc::Type::Str, "len".into(), true, "str::len".into());
+}
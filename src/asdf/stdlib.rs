@@ -0,0 +1,232 @@
+// the functions every instance gets, unless it's created with `Instance::without_stdlib`
+
+use std::{
+	fs::OpenOptions,
+	io::{Read, Write},
+	sync::OnceLock,
+	time::{Instant, SystemTime},
+};
+
+use crate::asdf::{
+	instance::Instance,
+	runtime::{with_runtime, Str},
+};
+
+pub fn define(instance: &mut Instance) {
+	instance.register_fn("print", print as extern "C" fn(_));
+	instance.register_fn("println", println as extern "C" fn(_));
+	instance.register_fn("to_string", to_string as extern "C" fn(_) -> _);
+	instance.register_fn("parse_int", parse_int as extern "C" fn(_) -> _);
+	instance.register_fn("is_int", is_int as extern "C" fn(_) -> _);
+	instance.register_fn("format", format as extern "C" fn(_, _) -> _);
+	instance.register_fn("format2", format2 as extern "C" fn(_, _, _) -> _);
+	instance.register_fn("format3", format3 as extern "C" fn(_, _, _, _) -> _);
+	instance.register_fn("read_line", read_line as extern "C" fn() -> _);
+	instance.register_fn("open", open as extern "C" fn(_, _) -> _);
+	instance.register_fn("read_file", read_file as extern "C" fn(_) -> _);
+	instance.register_fn("write_file", write_file as extern "C" fn(_, _) -> _);
+	instance.register_fn("close", close as extern "C" fn(_) -> _);
+	instance.register_fn("malloc", malloc as extern "C" fn(_) -> _);
+	instance.register_fn("free", free as extern "C" fn(_));
+	instance.register_fn("exit", exit as extern "C" fn(_));
+	instance.register_fn("abs", abs as extern "C" fn(_) -> _);
+	instance.register_fn("min", min as extern "C" fn(_, _) -> _);
+	instance.register_fn("max", max as extern "C" fn(_, _) -> _);
+	instance.register_fn("clock", clock as extern "C" fn() -> _);
+	instance.register_fn("time", time as extern "C" fn() -> _);
+	instance.register_fn("bp", bp as extern "C" fn());
+}
+
+fn as_str<'a>(x: *const Str) -> std::borrow::Cow<'a, str> {
+	return String::from_utf8_lossy(unsafe { (*x).as_bytes() });
+}
+
+extern "C" fn print(x: *const Str) {
+	print!("{}", as_str(x));
+}
+
+extern "C" fn println(x: *const Str) {
+	print!("{}\n", as_str(x));
+}
+
+extern "C" fn to_string(x: i64) -> *const Str {
+	return Str::new(x.to_string().into_bytes());
+}
+
+// invalid integers parse as 0, `is_int` tells them apart
+extern "C" fn parse_int(x: *const Str) -> i64 {
+	return as_str(x).trim().parse().unwrap_or(0);
+}
+
+extern "C" fn is_int(x: *const Str) -> i64 {
+	return as_str(x).trim().parse::<i64>().is_ok() as i64;
+}
+
+// every `{}` in the template is replaced by the next value
+fn format_values(template: *const Str, values: &[*const Str]) -> *const Str {
+	let template = as_str(template);
+	let mut parts = template.split("{}");
+
+	let mut ret = parts.next().unwrap_or_default().to_string();
+	let mut values = values.iter();
+
+	for part in parts {
+		match values.next() {
+			Some(x) => ret += &as_str(*x),
+			None => ret += "{}",
+		}
+
+		ret += part;
+	}
+
+	return Str::new(ret.into_bytes());
+}
+
+extern "C" fn format(template: *const Str, a: *const Str) -> *const Str {
+	return format_values(template, &[a]);
+}
+
+extern "C" fn format2(template: *const Str, a: *const Str, b: *const Str) -> *const Str {
+	return format_values(template, &[a, b]);
+}
+
+extern "C" fn format3(
+	template: *const Str,
+	a: *const Str,
+	b: *const Str,
+	c: *const Str,
+) -> *const Str {
+	return format_values(template, &[a, b, c]);
+}
+
+// without the line break, end of input reads as an empty line
+extern "C" fn read_line() -> *const Str {
+	let mut ret = String::new();
+	let _ = std::io::stdin().read_line(&mut ret);
+
+	let len = ret.trim_end_matches(['\n', '\r']).len();
+	ret.truncate(len);
+
+	return Str::new(ret.into_bytes());
+}
+
+// files are the descriptors `open` returned, -1 if they couldn't be opened, the ones still open
+// are closed along with the instance
+extern "C" fn open(path: *const Str, mode: *const Str) -> i64 {
+	let mut options = OpenOptions::new();
+
+	match as_str(mode).as_ref() {
+		"r" => options.read(true),
+		"w" => options.write(true).create(true).truncate(true),
+		"a" => options.append(true).create(true),
+		_ => return -1,
+	};
+
+	return match options.open(as_str(path).as_ref()) {
+		Ok(x) => with_runtime(|runtime| runtime.add_file(x)),
+		Err(_) => -1,
+	};
+}
+
+// a file that wasn't opened reads as empty
+extern "C" fn read_file(fd: i64) -> *const Str {
+	let mut ret = vec![];
+
+	with_runtime(|x| {
+		if let Some(file) = x.get_file(fd) {
+			let _ = file.read_to_end(&mut ret);
+		}
+	});
+
+	return Str::new(ret);
+}
+
+extern "C" fn write_file(fd: i64, data: *const Str) -> i64 {
+	let data = unsafe { (*data).as_bytes() };
+
+	return with_runtime(|x| match x.get_file(fd).map(|x| x.write_all(data)) {
+		Some(Ok(_)) => data.len() as i64,
+		_ => -1,
+	});
+}
+
+extern "C" fn close(fd: i64) -> i64 {
+	return match with_runtime(|x| x.remove_file(fd)) {
+		Some(_) => 0,
+		None => -1,
+	};
+}
+
+// the size is kept in front of the allocation, so `free` knows how much to deallocate
+extern "C" fn malloc(size: i64) -> *mut i64 {
+	let Some(layout) = usize::try_from(size)
+		.ok()
+		.and_then(|x| x.checked_add(8))
+		.and_then(|x| std::alloc::Layout::from_size_align(x, 8).ok())
+	else {
+		return std::ptr::null_mut();
+	};
+
+	unsafe {
+		let ptr = std::alloc::alloc(layout) as *mut i64;
+
+		if ptr.is_null() {
+			return ptr;
+		}
+
+		*ptr = size;
+		return ptr.add(1);
+	}
+}
+
+// the size in front of it is trusted, so `ptr` has to be null or have come from `malloc`
+extern "C" fn free(ptr: *mut i64) {
+	if ptr.is_null() {
+		return;
+	}
+
+	unsafe {
+		let ptr = ptr.sub(1);
+		let layout = std::alloc::Layout::from_size_align_unchecked(*ptr as usize + 8, 8);
+
+		std::alloc::dealloc(ptr as *mut u8, layout);
+	}
+}
+
+extern "C" fn exit(code: i64) {
+	let _ = std::io::stdout().flush();
+	std::process::exit(code as i32);
+}
+
+extern "C" fn abs(x: i64) -> i64 {
+	return x.wrapping_abs();
+}
+
+extern "C" fn min(a: i64, b: i64) -> i64 {
+	return a.min(b);
+}
+
+extern "C" fn max(a: i64, b: i64) -> i64 {
+	return a.max(b);
+}
+
+// nanoseconds since the first call, for measuring durations
+extern "C" fn clock() -> i64 {
+	static START: OnceLock<Instant> = OnceLock::new();
+	return START.get_or_init(Instant::now).elapsed().as_nanos() as i64;
+}
+
+// seconds since the unix epoch
+extern "C" fn time() -> i64 {
+	return SystemTime::now()
+		.duration_since(SystemTime::UNIX_EPOCH)
+		.map(|x| x.as_secs() as i64)
+		.unwrap_or(0);
+}
+
+#[naked]
+extern "C" fn bp() {
+	unsafe {
+		std::arch::asm!("int3", "ret", options(noreturn));
+	}
+}
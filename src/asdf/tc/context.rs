@@ -1,15 +1,44 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use crate::asdf::tc;
+use crate::asdf::{ast, location::Location, tc, Code, Diagnostic, Diagnostics, Result};
 
+#[derive(Clone)]
 pub struct Context {
 	defined_functions: HashMap<String, (tc::Type, Vec<tc::Type>)>,
+	function_locations: HashMap<String, Location>,
+	extern_functions: HashMap<String, (u64, tc::Type, Vec<tc::Type>)>,
+	generic_functions: HashMap<String, ast::Function>,
+	instances: HashSet<String>,
+	pending_instances: Vec<ast::Function>,
+	type_arguments: HashMap<String, tc::Type>,
+	return_type: tc::Type,
+	inference: tc::Inference,
+	traits: HashMap<String, Vec<(String, tc::Type)>>,
+	methods: HashMap<(tc::Type, String), Vec<(bool, String)>>,
+	imports: HashMap<Option<String>, HashSet<String>>,
+	private_functions: HashMap<String, String>,
+	module_name: Option<String>,
+	diagnostics: Diagnostics,
 }
 
 impl Context {
 	pub fn new() -> Self {
 		return Self {
 			defined_functions: HashMap::new(),
+			function_locations: HashMap::new(),
+			extern_functions: HashMap::new(),
+			generic_functions: HashMap::new(),
+			instances: HashSet::new(),
+			pending_instances: Vec::new(),
+			type_arguments: HashMap::new(),
+			return_type: tc::Type::None,
+			inference: tc::Inference::new(0),
+			traits: HashMap::new(),
+			methods: HashMap::new(),
+			imports: HashMap::new(),
+			private_functions: HashMap::new(),
+			module_name: None,
+			diagnostics: Diagnostics::new(),
 		};
 	}
 
@@ -23,6 +52,40 @@ impl Context {
 			.insert(function_name, (return_type, argument_types));
 	}
 
+	// the first definition of a name is kept, the later ones are reported pointing back at it
+	pub fn declare_function(
+		&mut self,
+		function_name: String,
+		location: &Location,
+		return_type: tc::Type,
+		argument_types: Vec<tc::Type>,
+	) {
+		if let Some(previous) = self.function_locations.get(&function_name) {
+			let diagnostic = Diagnostic::error(
+				Code::DuplicateDefinition,
+				format!("'{}' is defined more than once", function_name),
+			)
+			.with_primary(location.clone())
+			.with_secondary(previous.clone(), "first defined");
+
+			self.report(diagnostic.into());
+			return;
+		}
+
+		self.function_locations
+			.insert(function_name.clone(), location.clone());
+		self.define_function(function_name, return_type, argument_types);
+	}
+
+	pub fn forget_function(&mut self, function_name: &str) {
+		self.defined_functions.remove(function_name);
+		self.function_locations.remove(function_name);
+	}
+
+	pub fn find_function_location(&self, function_name: &str) -> Option<&Location> {
+		return self.function_locations.get(function_name);
+	}
+
 	pub fn find_function(&self, function_name: &str) -> Option<(tc::Type, Vec<tc::Type>)> {
 		let Some(ret) = self.defined_functions.get(function_name) else {
 			return None;
@@ -30,4 +93,284 @@ impl Context {
 
 		return Some(ret.clone());
 	}
+
+	// TODO: error handling
+	pub fn extern_function<T: Into<String>>(
+		&mut self,
+		name: T,
+		function: u64,
+		return_type: tc::Type,
+		argument_types: Vec<tc::Type>,
+	) {
+		self.extern_functions
+			.insert(name.into(), (function, return_type, argument_types));
+	}
+
+	// `main` takes either nothing, or the arguments and optionally the environment like in c
+	pub fn check_main(&self) -> Result<()> {
+		let Some((return_type, argument_types)) = self.find_function("main") else {
+			return Ok(());
+		};
+
+		let strings = tc::Type::Pointer(Box::new(tc::Type::Pointer(Box::new(tc::Type::Char))));
+
+		let is_valid = return_type == tc::Type::I64
+			&& [
+				vec![],
+				vec![tc::Type::I64, strings.clone()],
+				vec![tc::Type::I64, strings.clone(), strings],
+			]
+			.contains(&argument_types);
+
+		if !is_valid {
+			return Err(Diagnostic::error(
+				Code::InvalidMain,
+				format!(
+					"'main' should have the type 'fn() -> i64', 'fn(i64, **char) -> i64' or \
+				 'fn(i64, **char, **char) -> i64', got '{}'",
+					tc::Type::Function(Box::new(return_type), argument_types)
+				),
+			)
+			.into());
+		}
+
+		return Ok(());
+	}
+
+	pub fn find_extern_function(&self, name: &str) -> Option<(u64, tc::Type, Vec<tc::Type>)> {
+		let Some(ret) = self.extern_functions.get(name) else {
+			return None;
+		};
+
+		return Some(ret.clone());
+	}
+
+	pub fn define_trait(&mut self, trait_name: String, methods: Vec<(String, tc::Type)>) {
+		self.traits.insert(trait_name, methods);
+	}
+
+	pub fn find_trait(&self, trait_name: &str) -> Option<Vec<(String, tc::Type)>> {
+		let Some(ret) = self.traits.get(trait_name) else {
+			return None;
+		};
+
+		return Some(ret.clone());
+	}
+
+	pub fn define_method(
+		&mut self,
+		self_type: tc::Type,
+		method_name: String,
+		is_inherent: bool,
+		function_name: String,
+	) {
+		self.methods
+			.entry((self_type, method_name))
+			.or_default()
+			.push((is_inherent, function_name));
+	}
+
+	// inherent methods take precedence over the ones coming from traits
+	pub fn find_method(&self, self_type: &tc::Type, method_name: &str) -> Result<Option<String>> {
+		let Some(ret) = self
+			.methods
+			.get(&(self_type.clone(), method_name.to_string()))
+		else {
+			return Ok(None);
+		};
+
+		if let Some((_, function_name)) = ret.iter().find(|(is_inherent, _)| *is_inherent) {
+			return Ok(Some(function_name.clone()));
+		}
+
+		if ret.len() > 1 {
+			return Err(Diagnostic::error(
+				Code::AmbiguousMethod,
+				format!(
+					"multiple methods named '{}' found for '{}': {}",
+					method_name,
+					self_type,
+					ret.iter()
+						.map(|(_, x)| x.as_str())
+						.collect::<Vec<_>>()
+						.join(", ")
+				),
+			)
+			.into());
+		}
+
+		return Ok(ret.first().map(|(_, x)| x.clone()));
+	}
+
+	// `None` is the root module, which all of the added sources are a part of
+	pub fn define_module(&mut self, module_name: Option<String>, imports: Vec<String>) {
+		self.imports.entry(module_name).or_default().extend(imports);
+	}
+
+	pub fn define_private_function(&mut self, function_name: String, module_name: String) {
+		self.private_functions.insert(function_name, module_name);
+	}
+
+	pub fn set_module(&mut self, module_name: Option<String>) {
+		self.module_name = module_name;
+	}
+
+	// finds the function a name refers to from inside the current module, unqualified names
+	// are looked up in the current module first
+	pub fn qualify(&self, function_name: &str) -> Result<String> {
+		let Some((module_name, _)) = function_name.rsplit_once("::") else {
+			if let Some(current) = &self.module_name {
+				let qualified = format!("{}::{}", current, function_name);
+
+				if self.defined_functions.contains_key(&qualified) {
+					return Ok(qualified);
+				}
+			}
+
+			return Ok(function_name.to_string());
+		};
+
+		// paths like `str::len` name methods rather than modules
+		if !self.imports.contains_key(&Some(module_name.to_string())) {
+			return Ok(function_name.to_string());
+		}
+
+		if self.module_name.as_deref() == Some(module_name) {
+			return Ok(function_name.to_string());
+		}
+
+		if !self
+			.imports
+			.get(&self.module_name)
+			.is_some_and(|x| x.contains(module_name))
+		{
+			return Err(Diagnostic::error(
+				Code::NotImported,
+				format!(
+					"cannot use '{}' without importing '{}'",
+					function_name, module_name
+				),
+			)
+			.into());
+		}
+
+		if self.private_functions.contains_key(function_name) {
+			let mut diagnostic = Diagnostic::error(
+				Code::Private,
+				format!("'{}' is private to module '{}'", function_name, module_name),
+			);
+
+			if let Some(location) = self.find_function_location(function_name) {
+				diagnostic = diagnostic.with_secondary(location.clone(), "defined without 'pub'");
+			}
+
+			return Err(diagnostic.into());
+		}
+
+		return Ok(function_name.to_string());
+	}
+
+	pub fn define_generic_function(&mut self, function_name: String, function: ast::Function) {
+		self.generic_functions.insert(function_name, function);
+	}
+
+	pub fn find_type_parameters(&self, function_name: &str) -> Option<Vec<String>> {
+		let Some(ret) = self.generic_functions.get(function_name) else {
+			return None;
+		};
+
+		return Some(ret.get_type_parameters().to_vec());
+	}
+
+	// returns the mangled name of the specialized copy, queueing it up if it's new
+	pub fn instantiate(&mut self, function_name: &str, type_arguments: Vec<tc::Type>) -> String {
+		let template = self.generic_functions.get(function_name).unwrap();
+
+		let mangled_name = format!(
+			"{}<{}>",
+			function_name,
+			type_arguments
+				.iter()
+				.map(|x| x.to_string())
+				.collect::<Vec<_>>()
+				.join(", ")
+		);
+
+		if self.instances.insert(mangled_name.clone()) {
+			let type_arguments = template
+				.get_type_parameters()
+				.iter()
+				.cloned()
+				.zip(type_arguments)
+				.collect();
+
+			self.pending_instances
+				.push(template.instantiate(mangled_name.clone(), type_arguments));
+		}
+
+		return mangled_name;
+	}
+
+	pub fn check_instances(&mut self) -> Result<Vec<ast::Function>> {
+		let mut ret = vec![];
+
+		// checking an instance can queue up more instances
+		while let Some(mut function) = self.pending_instances.pop() {
+			match ast::Node::type_check(&mut function, self) {
+				Ok(_) => ret.push(function),
+				Err(x) => {
+					// so it's checked again the next time it's used, instead of being missing
+					self.instances.remove(function.get_name());
+					self.report(x);
+				}
+			}
+		}
+
+		return Ok(ret);
+	}
+
+	// errors that don't stop the checking, so more than one of them can be shown at once
+	pub fn report(&mut self, diagnostics: Diagnostics) {
+		self.diagnostics.extend(diagnostics);
+	}
+
+	pub fn error_count(&self) -> usize {
+		return self.diagnostics.len();
+	}
+
+	pub fn take_diagnostics(&mut self) -> Diagnostics {
+		return std::mem::take(&mut self.diagnostics);
+	}
+
+	pub fn set_type_arguments(&mut self, type_arguments: HashMap<String, tc::Type>) {
+		self.type_arguments = type_arguments;
+	}
+
+	pub fn substitute(&self, typ: &tc::Type) -> tc::Type {
+		return typ.substitute(&self.type_arguments);
+	}
+
+	pub fn set_return_type(&mut self, return_type: tc::Type) {
+		self.return_type = return_type;
+	}
+
+	pub fn get_return_type(&self) -> &tc::Type {
+		return &self.return_type;
+	}
+
+	pub fn start_inference(&mut self, variable_count: usize) {
+		self.inference = tc::Inference::new(variable_count);
+	}
+
+	pub fn new_variable(&mut self) -> tc::Type {
+		return self.inference.new_variable();
+	}
+
+	pub fn unify(&mut self, expected: &tc::Type, got: &tc::Type) -> Result<()> {
+		return self.inference.unify(expected, got);
+	}
+
+	pub fn resolve(&self, typ: &tc::Type) -> tc::Type {
+		return self.inference.resolve(typ);
+	}
 }
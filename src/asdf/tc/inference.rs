@@ -0,0 +1,78 @@
+use crate::asdf::{tc, Code, Diagnostic, Result};
+
+#[derive(Clone)]
+pub struct Inference {
+	variables: Vec<Option<tc::Type>>,
+}
+
+impl Inference {
+	pub fn new(variable_count: usize) -> Self {
+		return Self {
+			variables: vec![None; variable_count],
+		};
+	}
+
+	pub fn new_variable(&mut self) -> tc::Type {
+		self.variables.push(None);
+		return tc::Type::Variable(self.variables.len() - 1);
+	}
+
+	pub fn resolve(&self, typ: &tc::Type) -> tc::Type {
+		return match typ {
+			tc::Type::Variable(x) => match &self.variables[*x] {
+				Some(x) => self.resolve(x),
+				None => typ.clone(),
+			},
+			tc::Type::Reference(x) => tc::Type::Reference(Box::new(self.resolve(x))),
+			tc::Type::Pointer(x) => tc::Type::Pointer(Box::new(self.resolve(x))),
+			tc::Type::Function(ret, args) => tc::Type::Function(
+				Box::new(self.resolve(ret)),
+				args.iter().map(|x| self.resolve(x)).collect(),
+			),
+			_ => typ.clone(),
+		};
+	}
+
+	pub fn unify(&mut self, expected: &tc::Type, got: &tc::Type) -> Result<()> {
+		let expected = self.resolve(expected);
+		let got = self.resolve(got);
+
+		return match (&expected, &got) {
+			(tc::Type::Variable(x), tc::Type::Variable(y)) if x == y => Ok(()),
+			(tc::Type::Variable(x), _) => self.bind(*x, got),
+			(_, tc::Type::Variable(y)) => self.bind(*y, expected),
+			(tc::Type::Reference(x), tc::Type::Reference(y)) => self.unify(x, y),
+			(tc::Type::Pointer(x), tc::Type::Pointer(y)) => self.unify(x, y),
+			(tc::Type::Function(x_ret, x_args), tc::Type::Function(y_ret, y_args))
+				if x_args.len() == y_args.len() =>
+			{
+				self.unify(x_ret, y_ret)?;
+
+				for (x, y) in x_args.iter().zip(y_args) {
+					self.unify(x, y)?;
+				}
+
+				Ok(())
+			}
+			_ if expected == got => Ok(()),
+			_ => Err(Diagnostic::error(
+				Code::TypeMismatch,
+				format!("expected '{}', got '{}'", expected, got),
+			)
+			.into()),
+		};
+	}
+
+	fn bind(&mut self, variable: usize, typ: tc::Type) -> Result<()> {
+		if typ.contains_variable(variable) {
+			return Err(Diagnostic::error(
+				Code::InfiniteType,
+				format!("cannot construct the infinite type '{}'", typ),
+			)
+			.into());
+		}
+
+		self.variables[variable] = Some(typ);
+		return Ok(());
+	}
+}
@@ -1,5 +1,7 @@
 mod context;
+mod inference;
 mod r#type;
 
 pub use context::*;
+pub use inference::*;
 pub use r#type::*;
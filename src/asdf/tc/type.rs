@@ -1,22 +1,100 @@
-#[derive(Debug, Clone, PartialEq)]
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Type {
 	None,
 	I64,
 	Char,
+	Str,
 	Reference(Box<Self>),
 	Pointer(Box<Self>),
 	Function(Box<Self>, Vec<Self>),
+	Generic(String),
+	Variable(usize),
 }
 
 impl Type {
 	pub fn get_size(&self) -> usize {
 		match *self {
 			Type::I64 => 8,
-			Type::Char => 1,
+			// chars are widened to a full register
+			Type::Char => 8,
+			Type::Str => 8,
 			Type::Reference(_) => 8,
 			Type::Pointer(_) => 8,
 			Type::Function(_, _) => 8,
-			Type::None => unreachable!(),
+			// every instantiation is passed around in a 64-bit register
+			Type::Generic(_) => 8,
+			Type::Variable(_) => 8,
+			// nothing is stored in it, but it still gets a slot like everything else
+			Type::None => 8,
+		}
+	}
+
+	pub fn substitute(&self, type_arguments: &HashMap<String, Type>) -> Type {
+		match self {
+			Type::Generic(name) => match type_arguments.get(name) {
+				Some(x) => x.clone(),
+				None => self.clone(),
+			},
+			Type::Reference(x) => Type::Reference(Box::new(x.substitute(type_arguments))),
+			Type::Pointer(x) => Type::Pointer(Box::new(x.substitute(type_arguments))),
+			Type::Function(ret, args) => Type::Function(
+				Box::new(ret.substitute(type_arguments)),
+				args.iter().map(|x| x.substitute(type_arguments)).collect(),
+			),
+			_ => self.clone(),
+		}
+	}
+
+	pub fn contains_variable(&self, variable: usize) -> bool {
+		match self {
+			Type::Variable(x) => *x == variable,
+			Type::Reference(x) => x.contains_variable(variable),
+			Type::Pointer(x) => x.contains_variable(variable),
+			Type::Function(ret, args) => {
+				ret.contains_variable(variable)
+					|| args.iter().any(|x| x.contains_variable(variable))
+			}
+			_ => false,
+		}
+	}
+
+	pub fn is_resolved(&self) -> bool {
+		match self {
+			Type::Variable(_) => false,
+			Type::Reference(x) => x.is_resolved(),
+			Type::Pointer(x) => x.is_resolved(),
+			Type::Function(ret, args) => ret.is_resolved() && args.iter().all(|x| x.is_resolved()),
+			_ => true,
+		}
+	}
+}
+
+impl std::fmt::Display for Type {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Type::None => write!(f, "()"),
+			Type::I64 => write!(f, "i64"),
+			Type::Char => write!(f, "char"),
+			Type::Str => write!(f, "str"),
+			Type::Reference(x) => write!(f, "&{x}"),
+			Type::Pointer(x) => write!(f, "*{x}"),
+			Type::Function(ret, args) => {
+				write!(f, "fn(")?;
+
+				for (i, x) in args.iter().enumerate() {
+					if i != 0 {
+						write!(f, ", ")?;
+					}
+
+					write!(f, "{x}")?;
+				}
+
+				write!(f, ") -> {ret}")
+			}
+			Type::Generic(name) => write!(f, "{name}"),
+			Type::Variable(_) => write!(f, "_"),
 		}
 	}
 }
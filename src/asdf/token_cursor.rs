@@ -0,0 +1,126 @@
+use crate::asdf::{location::Location, tokens::Tokens, Result};
+
+// everything after parsing recurses as deep as the ast goes, so it's limited before the stack
+// would run out
+const MAX_DEPTH: usize = 256;
+
+// the tokens of a whole file, shared by the statement and the expression parser
+pub struct TokenCursor {
+	tokens: Vec<(Tokens, Location)>,
+	position: usize,
+	// where errors point to before anything has been popped
+	start: Location,
+	// of the blocks and values being parsed
+	depth: usize,
+}
+
+impl TokenCursor {
+	pub fn new(tokens: Vec<(Tokens, Location)>) -> Self {
+		let start = tokens.first().map(|(_, x)| x.clone()).unwrap_or_default();
+
+		return Self {
+			tokens,
+			position: 0,
+			start,
+			depth: 0,
+		};
+	}
+
+	pub fn is_empty(&self) -> bool {
+		return self.position >= self.tokens.len();
+	}
+
+	pub fn get_position(&self) -> usize {
+		return self.position;
+	}
+
+	// the last token that was popped, errors point to it
+	pub fn get_location(&self) -> &Location {
+		let Some((_, ret)) = self
+			.position
+			.checked_sub(1)
+			.and_then(|x| self.tokens.get(x))
+		else {
+			return &self.start;
+		};
+
+		return ret;
+	}
+
+	pub fn get_last_token(&self) -> Option<&Tokens> {
+		let (ret, _) = self.tokens.get(self.position.checked_sub(1)?)?;
+		return Some(ret);
+	}
+
+	pub fn peek(&self) -> Option<Tokens> {
+		return self.peek_nth(0);
+	}
+
+	pub fn peek_nth(&self, index: usize) -> Option<Tokens> {
+		let (ret, _) = self.tokens.get(self.position + index)?;
+		return Some(ret.clone());
+	}
+
+	// where the next token is, or the last one if there's nothing left
+	pub fn peek_location(&self) -> Location {
+		let Some((_, ret)) = self.tokens.get(self.position) else {
+			return self.get_location().clone();
+		};
+
+		return ret.clone();
+	}
+
+	// the next token for error messages
+	pub fn describe_next(&self) -> String {
+		return match self.peek() {
+			Some(x) => x.to_string(),
+			None => format!("nothing"),
+		};
+	}
+
+	// every `enter` has to be followed by a `leave`, whether parsing failed or not
+	pub fn enter(&mut self) -> Result<()> {
+		if self.depth >= MAX_DEPTH {
+			return self
+				.peek_location()
+				.error(format!("nested more than {} levels deep", MAX_DEPTH));
+		}
+
+		self.depth += 1;
+		return Ok(());
+	}
+
+	pub fn leave(&mut self) {
+		self.depth -= 1;
+	}
+
+	pub fn pop(&mut self) -> Option<(Tokens, Location)> {
+		let ret = self.tokens.get(self.position)?.clone();
+		self.position += 1;
+
+		return Some(ret);
+	}
+
+	pub fn pop_checked(&mut self, expected: Tokens) -> Result<()> {
+		return match self.pop() {
+			Some((tok, _)) if tok == expected => Ok(()),
+			x => self.unexpected(&expected.to_string(), x),
+		};
+	}
+
+	// `found` is the token that was just popped, it's put back so recovering can look at it
+	pub fn unexpected<T>(
+		&mut self,
+		expected: &str,
+		found: Option<(Tokens, Location)>,
+	) -> Result<T> {
+		let Some((tok, location)) = found else {
+			return self
+				.get_location()
+				.error(format!("expected {}, found nothing", expected));
+		};
+
+		self.position -= 1;
+		return location.error(format!("expected {}, found {}", expected, tok));
+	}
+}
@@ -1,3 +1,5 @@
+use std::fmt::{Display, Formatter};
+
 #[allow(dead_code)]
 #[derive(Debug, Clone, PartialEq)]
 pub enum Tokens {
@@ -9,12 +11,23 @@ pub enum Tokens {
 	Mut,
 	Imm,
 	Fn,
+	Trait,
+	Impl,
+	For,
+	Import,
+	Pub,
 	Integer(i64),
 	String(String),
 	Identifier(String),
+	// the text of a `///` line, without the slashes
+	DocComment(String),
+	// any other comment, with the slashes and stars
+	Comment(String),
 	SemiColon,
 	Colon,
+	ColonColon,
 	Comma,
+	Dot,
 	Plus,
 	Minus,
 	Star,
@@ -24,9 +37,68 @@ pub enum Tokens {
 	Percent,
 	And,
 	Pipe,
+	Hash,
 	ParenOpen,
 	ParenClose,
 	CurlyOpen,
 	CurlyClose,
+	BracketOpen,
+	BracketClose,
+	LessThan,
+	GreaterThan,
 	I64,
+	Char,
+	Str,
+}
+
+// the way tokens are shown in syntax errors
+impl Display for Tokens {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		return match self {
+			Tokens::Invalid => write!(f, "an invalid token"),
+			Tokens::If => write!(f, "'if'"),
+			Tokens::While => write!(f, "'while'"),
+			Tokens::Else => write!(f, "'else'"),
+			Tokens::Return => write!(f, "'return'"),
+			Tokens::Mut => write!(f, "'mut'"),
+			Tokens::Imm => write!(f, "'imm'"),
+			Tokens::Fn => write!(f, "'fn'"),
+			Tokens::Trait => write!(f, "'trait'"),
+			Tokens::Impl => write!(f, "'impl'"),
+			Tokens::For => write!(f, "'for'"),
+			Tokens::Import => write!(f, "'import'"),
+			Tokens::Pub => write!(f, "'pub'"),
+			Tokens::Integer(x) => write!(f, "'{}'", x),
+			Tokens::String(x) => write!(f, "{:?}", x),
+			Tokens::Identifier(x) => write!(f, "'{}'", x),
+			Tokens::DocComment(_) => write!(f, "a doc comment"),
+			Tokens::Comment(_) => write!(f, "a comment"),
+			Tokens::SemiColon => write!(f, "';'"),
+			Tokens::Colon => write!(f, "':'"),
+			Tokens::ColonColon => write!(f, "'::'"),
+			Tokens::Comma => write!(f, "','"),
+			Tokens::Dot => write!(f, "'.'"),
+			Tokens::Plus => write!(f, "'+'"),
+			Tokens::Minus => write!(f, "'-'"),
+			Tokens::Star => write!(f, "'*'"),
+			Tokens::Slash => write!(f, "'/'"),
+			Tokens::Equals => write!(f, "'='"),
+			Tokens::ExclamationMark => write!(f, "'!'"),
+			Tokens::Percent => write!(f, "'%'"),
+			Tokens::And => write!(f, "'&'"),
+			Tokens::Pipe => write!(f, "'|'"),
+			Tokens::Hash => write!(f, "'#'"),
+			Tokens::ParenOpen => write!(f, "'('"),
+			Tokens::ParenClose => write!(f, "')'"),
+			Tokens::CurlyOpen => write!(f, "'{{'"),
+			Tokens::CurlyClose => write!(f, "'}}'"),
+			Tokens::BracketOpen => write!(f, "'['"),
+			Tokens::BracketClose => write!(f, "']'"),
+			Tokens::LessThan => write!(f, "'<'"),
+			Tokens::GreaterThan => write!(f, "'>'"),
+			Tokens::I64 => write!(f, "'i64'"),
+			Tokens::Char => write!(f, "'char'"),
+			Tokens::Str => write!(f, "'str'"),
+		};
+	}
 }
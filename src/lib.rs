@@ -0,0 +1,8 @@
+#![feature(naked_functions)]
+
+mod asdf;
+
+#[cfg(test)]
+mod tests;
+
+pub use asdf::*;
@@ -1,59 +1,182 @@
-#![feature(naked_functions)]
+use std::{io::Write, path::Path};
+
+use asdf::{instance::Instance, FileSystemProvider, Result, Str, Type};
+
+const USAGE: &str = "usage:
+	asdf run <file> [args...]
+	asdf check <file>
+	asdf dump-hex <file>
+	asdf fmt <file>
+	asdf repl";
+
+fn main() {
+	let args: Vec<String> = std::env::args().skip(1).collect();
+
+	let ret = match args
+		.iter()
+		.map(|x| x.as_str())
+		.collect::<Vec<_>>()
+		.as_slice()
+	{
+		["run", file_name, arguments @ ..] => run(file_name, arguments),
+		["check", file_name] => check(file_name),
+		["dump-hex", file_name] => dump_hex(file_name),
+		["fmt", file_name] => fmt(file_name),
+		["repl"] => repl(),
+		_ => {
+			eprintln!("{}", USAGE);
+			std::process::exit(2);
+		}
+	};
+
+	match ret {
+		Ok(code) => std::process::exit(code),
+		Err(x) => {
+			eprintln!("{}", x);
+			std::process::exit(1);
+		}
+	}
+}
+
+fn run(file_name: &str, arguments: &[&str]) -> Result<i32> {
+	let mut instance = load(file_name)?;
+	instance.compile()?;
+	print_warnings(&instance);
 
-mod asdf;
+	// the script itself takes the place of the program name
+	let arguments = [&[file_name], arguments].concat();
 
-#[cfg(test)]
-mod tests;
+	// only the lowest byte makes it to the shell anyway
+	return Ok(instance.execute(&arguments)? as i32);
+}
+
+fn check(file_name: &str) -> Result<i32> {
+	let mut instance = load(file_name)?;
+	instance.check()?;
+	print_warnings(&instance);
+
+	return Ok(0);
+}
 
-use asdf::instance::Instance;
+fn dump_hex(file_name: &str) -> Result<i32> {
+	let mut instance = load(file_name)?;
+	instance.compile()?;
+
+	print!("{}", instance.dump_hex());
+	return Ok(0);
+}
+
+// rewrites the file in place, imports are left alone
+fn fmt(file_name: &str) -> Result<i32> {
+	let source = read(file_name)?;
+	let formatted = asdf::format(file_name, &source)?;
+
+	if formatted != source {
+		std::fs::write(file_name, formatted)
+			.map_err(|x| format!("cannot write '{}': {}", file_name, x))?;
+	}
+
+	return Ok(0);
+}
+
+fn repl() -> Result<i32> {
+	let mut instance = Instance::new();
+	instance.set_source_provider(FileSystemProvider::new("."));
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-	let string = r#"
+	let mut definitions = 0;
 
-		fn main() {
-			fizz_buzz();
-			return zxcv();
+	while let Some(input) = read_input()? {
+		let input = input.trim();
+
+		if input.is_empty() {
+			continue;
 		}
 
-		fn zxcv() {
-			print("hello world!\n");
-			return 5;
+		let is_definition = ["fn ", "pub ", "impl ", "trait ", "import "]
+			.iter()
+			.any(|x| input.starts_with(x));
+
+		if !is_definition {
+			match instance.evaluate(input.trim_end_matches(';')) {
+				Ok((value, typ)) => println!("{}: {}", format_value(value, &typ), typ),
+				Err(x) => eprintln!("{}", x),
+			}
+
+			continue;
 		}
 
-		fn fizz_buzz() {
+		definitions += 1;
+		let file_name = format!("<repl {}>", definitions);
 
-			mut i = 1;
+		// a definition that doesn't compile is dropped, so it won't break the following ones
+		if let Err(x) = instance.extend(&file_name, input) {
+			eprintln!("{}", x);
+		}
+	}
 
-			while i != 100 {
+	return Ok(0);
+}
 
-				mut first = (i % 3) == 0;
-				mut second = (i % 5) == 0;
+// reads lines until every opened brace is closed
+fn read_input() -> Result<Option<String>> {
+	let mut ret = String::new();
+	let mut depth = 0;
 
-				if first {
-					print("fizz");
-				}
+	loop {
+		print!("{}", if ret.is_empty() { "> " } else { ". " });
+		std::io::stdout().flush().map_err(|x| x.to_string())?;
 
-				if second {
-					print("buzz");
-				}
+		let mut line = String::new();
 
-				if first == 0 && second == 0 {
-					print(to_string(i));
-				}
+		if std::io::stdin()
+			.read_line(&mut line)
+			.map_err(|x| x.to_string())?
+			== 0
+		{
+			return Ok(None);
+		}
 
-				print("\n");
-				i = i + 1;
-			}
+		depth += line.matches('{').count() as i64 - line.matches('}').count() as i64;
+		ret += &line;
 
-			return 0;
+		if depth <= 0 {
+			return Ok(Some(ret));
 		}
-	"#;
+	}
+}
+
+fn format_value(value: i64, typ: &Type) -> String {
+	return match typ {
+		Type::None => format!("()"),
+		Type::I64 => value.to_string(),
+		Type::Char => format!("{:?}", value as u8 as char),
+		Type::Str => {
+			let value = unsafe { (*(value as *const Str)).as_bytes() };
+			format!("{:?}", String::from_utf8_lossy(value))
+		}
+		_ => format!("{:#x}", value),
+	};
+}
+
+fn print_warnings(instance: &Instance) {
+	if !instance.get_warnings().is_empty() {
+		eprintln!("{}\n", instance.get_warnings());
+	}
+}
+
+// imports are looked up next to the file itself
+fn load(file_name: &str) -> Result<Instance> {
+	let source = read(file_name)?;
+	let directory = Path::new(file_name).parent().unwrap_or(Path::new("."));
 
 	let mut instance = Instance::new();
-	instance.parse(string)?;
+	instance.set_source_provider(FileSystemProvider::new(directory));
+	instance.add_source(file_name, &source)?;
 
-	let ret = instance.execute();
-	print!("ret: {}\n", ret);
+	return Ok(instance);
+}
 
-	return Ok(());
+fn read(file_name: &str) -> Result<String> {
+	return Ok(std::fs::read_to_string(file_name)
+		.map_err(|x| format!("cannot read '{}': {}", file_name, x))?);
 }
@@ -1,11 +1,13 @@
-use crate::asdf::{instance::Instance, Result};
+use crate::asdf::{
+	instance::Instance, CodeMemory, FileSystemProvider, MemoryProvider, Result, Str, Type,
+};
 
 // parse and run
 fn par(string: &str) -> Result<i64> {
 	let mut instance = Instance::new();
 	instance.parse(string)?;
 
-	return Ok(instance.execute());
+	return instance.execute(&[]);
 }
 
 #[test]
@@ -228,3 +230,1451 @@ fn function_params() -> Result<()> {
 
 	Ok(())
 }
+
+#[test]
+fn generic_functions() -> Result<()> {
+	assert_eq!(
+		par(r#"
+
+		fn main() {
+			return max(3, 7) + min(10, 2) + first(max(1, 2), "x") + first(4, 5) - 4;
+		}
+
+		fn max<T>(a: T, b: T) -> T {
+			if a > b {
+				return a;
+			}
+
+			return b;
+		}
+
+		fn min<T>(a: T, b: T) -> T {
+			if a < b {
+				return a;
+			}
+
+			return b;
+		}
+
+		fn first<T, U>(a: T, b: U) -> T {
+			return a;
+		}
+
+	"#)?,
+		11
+	);
+
+	assert!(par(r#"
+
+		fn main() {
+			return max(1, "a");
+		}
+
+		fn max<T>(a: T, b: T) -> T {
+			return a;
+		}
+
+	"#)
+	.is_err());
+
+	Ok(())
+}
+
+#[test]
+fn type_inference() -> Result<()> {
+	assert_eq!(
+		par(r#"
+
+		fn main() {
+			mut x;
+			x = 5;
+
+			mut y = later(x);
+			y = y + x;
+
+			return y;
+		}
+
+		fn later(x: i64) {
+			return x * 2;
+		}
+
+	"#)?,
+		15
+	);
+
+	assert!(par(r#"
+
+		fn main() {
+			mut x;
+			return 0;
+		}
+
+	"#)
+	.is_err());
+
+	Ok(())
+}
+
+#[test]
+fn traits() -> Result<()> {
+	assert_eq!(
+		par(r#"
+
+		trait Shape {
+			fn area(self: Self) -> i64;
+			fn scaled(self: Self, factor: i64) -> Self;
+		}
+
+		impl Shape for i64 {
+			fn area(self: i64) -> i64 {
+				return self * self;
+			}
+
+			fn scaled(self: i64, factor: i64) -> i64 {
+				return self * factor;
+			}
+		}
+
+		fn main() {
+			mut x = 3;
+			return x.area() + x.scaled(2).area() + 4.area();
+		}
+
+	"#)?,
+		61
+	);
+
+	assert!(par(r#"
+
+		trait Shape {
+			fn area(self: Self) -> i64;
+			fn scaled(self: Self, factor: i64) -> Self;
+		}
+
+		impl Shape for i64 {
+			fn area(self: i64) -> i64 {
+				return self * self;
+			}
+		}
+
+		fn main() {
+			return 0;
+		}
+
+	"#)
+	.unwrap_err()
+	.to_string()
+	.contains("missing method 'scaled'"));
+
+	Ok(())
+}
+
+#[test]
+fn methods() -> Result<()> {
+	assert_eq!(
+		par(r#"
+
+		impl i64 {
+			fn double(self: i64) -> i64 {
+				return self * 2;
+			}
+
+			fn increment(self: *i64) -> i64 {
+				*self = *self + 1;
+				return *self;
+			}
+		}
+
+		fn main() {
+			mut x = 5;
+
+			x.increment();
+			x.increment();
+
+			return x.double() + x;
+		}
+
+	"#)?,
+		21
+	);
+
+	// the receiver of each call in a chain is only checked once
+	assert_eq!(
+		par(&format!(
+			"impl i64 {{ fn double(self: i64) -> i64 {{ return self * 2; }} }} \
+			 fn main() {{ imm x = 1; return x{}; }}",
+			".double()".repeat(40)
+		))?,
+		1 << 40
+	);
+
+	// every method is checked, not just the ones before the first error, and so is 'main'
+	let error = Instance::new()
+		.parse(
+			r#"
+
+		impl i64 {
+			fn first(self: i64, a: i64, b: i64, c: i64, d: i64, e: i64, f: i64) {}
+			fn second(self: i64, a: i64, b: i64, c: i64, d: i64, e: i64, f: i64) {}
+		}
+
+		fn main(x: str) {}
+
+	"#,
+		)
+		.unwrap_err();
+
+	let mut messages = error.iter().map(|x| x.get_message()).collect::<Vec<_>>();
+	messages.sort();
+
+	assert_eq!(
+		messages,
+		[
+			"'i64::first' takes more than 6 parameters",
+			"'i64::second' takes more than 6 parameters",
+			"'main' should have the type 'fn() -> i64', 'fn(i64, **char) -> i64' or \
+			 'fn(i64, **char, **char) -> i64', got 'fn(str) -> i64'",
+		]
+	);
+
+	Ok(())
+}
+
+#[test]
+fn strings() -> Result<()> {
+	assert_eq!(
+		par(r#"
+
+		fn main() {
+			mut greeting = "hello" + ", " + "world";
+			mut ret = greeting.len();
+
+			if greeting == "hello, world" {
+				ret = ret + 100;
+			}
+
+			if greeting != "hello" {
+				ret = ret + 1000;
+			}
+
+			if greeting[4] == "o"[0] {
+				ret = ret + 10000;
+			}
+
+			return ret;
+		}
+
+	"#)?,
+		11112
+	);
+
+	assert_eq!(
+		par(r#"
+
+		fn main() {
+			return "\t\"\\\x41\u{e9}".len();
+		}
+
+	"#)?,
+		6
+	);
+
+	assert!(par(r#"
+
+		fn main() {
+			return "a" - "b";
+		}
+
+	"#)
+	.is_err());
+
+	let mut instance = Instance::new();
+
+	instance.parse(
+		r#"
+
+		fn is_b(x: str, i: i64) -> i64 {
+			return x[i] == "b"[0];
+		}
+
+		fn main() {
+			return is_b("abc", 3);
+		}
+
+	"#,
+	)?;
+
+	let error = instance.execute(&[]).unwrap_err();
+
+	assert_eq!(
+		error.iter().map(|x| x.get_message()).collect::<Vec<_>>(),
+		["index 3 is out of bounds for a str of length 3"]
+	);
+
+	// the error doesn't stick around for the next call
+	assert_eq!(
+		instance.call::<_, i64>("is_b", (Str::new(b"abc".to_vec()), 1))?,
+		1
+	);
+
+	Ok(())
+}
+
+#[test]
+fn host_functions() -> Result<()> {
+	extern "C" fn clamp(x: i64, min: i64, max: i64) -> i64 {
+		return x.clamp(min, max);
+	}
+
+	extern "C" fn shout(x: *const Str) -> i64 {
+		return unsafe { (*x).as_bytes() }.len() as i64 * 10;
+	}
+
+	let mut instance = Instance::new();
+	instance.register_fn("clamp", clamp as extern "C" fn(_, _, _) -> _);
+	instance.register_fn("shout", shout as extern "C" fn(_) -> _);
+
+	instance.parse(
+		r#"
+
+		fn sum(a: i64, b: i64, c: i64, d: i64, e: i64, f: i64) -> i64 {
+			return a + b * 2 + c * 3 + d * 4 + e * 5 + f * 6;
+		}
+
+		fn main() {
+			return sum(clamp(50, 0, 10), 1, 1, 1, 1, shout("abc")) + clamp(0 - 5, 0, 10);
+		}
+
+	"#,
+	)?;
+
+	assert_eq!(instance.execute(&[])?, 10 + 2 + 3 + 4 + 5 + 180);
+
+	let mut instance = Instance::new();
+
+	let error = instance
+		.parse(
+			r#"
+
+		fn main() {
+			return clamp(1, 2, 3);
+		}
+
+	"#,
+		)
+		.unwrap_err()
+		.to_string();
+
+	assert!(error.contains("clamp"), "{}", error);
+	Ok(())
+}
+
+#[test]
+fn calling_from_rust() -> Result<()> {
+	let mut instance = Instance::new();
+
+	instance.parse(
+		r#"
+
+		fn score(a: i64, b: i64) -> i64 {
+			return a * 10 + b;
+		}
+
+		fn is_valid(x: str) -> i64 {
+			return x.len() > 2;
+		}
+
+		fn main() {
+			return 0;
+		}
+
+	"#,
+	)?;
+
+	assert_eq!(instance.call::<(i64, i64), i64>("score", (4, 2))?, 42);
+	assert_eq!(instance.call::<_, i64>("score", (-1, 3))?, -7);
+
+	let literal = Str::new(b"abc".to_vec());
+	assert_eq!(instance.call::<_, i64>("is_valid", (literal,))?, 1);
+
+	let error = instance
+		.call::<(i64,), i64>("score", (1,))
+		.unwrap_err()
+		.to_string();
+	assert!(error.contains("fn(i64, i64) -> i64"), "{}", error);
+
+	let error = instance
+		.call::<(), i64>("missing", ())
+		.unwrap_err()
+		.to_string();
+	assert!(
+		error.contains("cannot find function 'missing'"),
+		"{}",
+		error
+	);
+
+	Ok(())
+}
+
+#[test]
+fn repeated_calls() -> Result<()> {
+	let mut instance = Instance::new();
+
+	instance.parse(
+		r#"
+
+		fn square(x: i64) -> i64 {
+			return x * x;
+		}
+
+		fn main() {
+			return square(7);
+		}
+
+	"#,
+	)?;
+
+	for i in 0..1000 {
+		assert_eq!(instance.call::<_, i64>("square", (i,))?, i * i);
+	}
+
+	assert_eq!(instance.execute(&[])?, 49);
+	assert_eq!(instance.execute(&[])?, 49);
+
+	Ok(())
+}
+
+#[test]
+fn code_memory() -> Result<()> {
+	// mov rax, 42; ret
+	let code = [0x48, 0xC7, 0xC0, 42, 0x00, 0x00, 0x00, 0xC3];
+
+	let mut memory = CodeMemory::new(&code)?;
+	let function: extern "C" fn() -> i64 = unsafe { std::mem::transmute(memory.get_address(0)) };
+
+	assert_eq!(function(), 42);
+	assert!(unsafe { memory.patch(3, &[43]) }.is_err());
+
+	let mut memory = CodeMemory::new_dual_mapped(&code)?;
+	let function: extern "C" fn() -> i64 = unsafe { std::mem::transmute(memory.get_address(0)) };
+
+	assert_eq!(function(), 42);
+
+	unsafe { memory.patch(3, &[43])? };
+	assert_eq!(function(), 43);
+
+	assert!(unsafe { memory.patch(6, &[0x90; 4]) }.is_err());
+	assert!(unsafe { memory.patch(usize::MAX, &[0x90]) }.is_err());
+	assert!(CodeMemory::new(&[]).is_err());
+
+	Ok(())
+}
+
+#[test]
+fn multiple_sources() -> Result<()> {
+	let mut instance = Instance::new();
+
+	instance.add_source(
+		"math.asdf",
+		r#"
+
+		fn square(x: i64) -> i64 {
+			return x * x;
+		}
+
+		fn cube(x: i64) -> i64 {
+			return square(x) * x;
+		}
+
+	"#,
+	)?;
+
+	instance.add_source(
+		"main.asdf",
+		r#"
+
+		fn main() {
+			return cube(3) + square(2);
+		}
+
+	"#,
+	)?;
+
+	instance.compile()?;
+	assert_eq!(instance.execute(&[])?, 31);
+
+	let mut instance = Instance::new();
+	instance.add_source("a.asdf", "fn f() { return 1; }")?;
+	instance.add_source("b.asdf", "fn f() { return 2; }")?;
+	instance.add_source("main.asdf", "fn main() { return f(); }")?;
+
+	let error = instance.compile().unwrap_err().to_string();
+	assert!(error.contains("'f' is defined more than once"), "{}", error);
+	assert!(error.contains(" --> b.asdf:1:1\n"), "{}", error);
+	assert!(
+		error.contains("= note: first defined at a.asdf:1:1"),
+		"{}",
+		error
+	);
+
+	let error = Instance::new()
+		.add_source("broken.asdf", "fn main() {\n\treturn \"\\q\";\n}")
+		.unwrap_err()
+		.to_string();
+
+	assert!(error.contains(" --> broken.asdf:2:10\n"), "{}", error);
+	assert!(error.ends_with("  |             ^^"), "{}", error);
+	Ok(())
+}
+
+#[test]
+fn modules() -> Result<()> {
+	let mut provider = MemoryProvider::new();
+
+	provider.add_module(
+		"math",
+		r#"
+
+		fn twice(x: i64) -> i64 {
+			return x * 2;
+		}
+
+		pub fn square(x: i64) -> i64 {
+			return (twice(x) * x) / 2;
+		}
+
+		pub fn max<T>(a: T, b: T) -> T {
+			if a > b {
+				return a;
+			}
+
+			return b;
+		}
+
+	"#,
+	);
+
+	provider.add_module(
+		"geometry",
+		r#"
+
+		import math;
+
+		pub fn area(w: i64, h: i64) -> i64 {
+			return math::max(w, h) * math::max(h, w);
+		}
+
+	"#,
+	);
+
+	let mut instance = Instance::new();
+	instance.set_source_provider(provider);
+
+	instance.parse(
+		r#"
+
+		import math;
+		import geometry;
+
+		fn main() {
+			return math::square(3) + geometry::area(2, 5);
+		}
+
+	"#,
+	)?;
+
+	assert_eq!(instance.execute(&[])?, 34);
+	assert_eq!(instance.call::<_, i64>("math::square", (4,))?, 16);
+
+	// `pub` functions are what a module is for, whether they're used or not
+	let mut provider = MemoryProvider::new();
+	provider.add_module(
+		"math",
+		"pub fn cube(x: i64) -> i64 { return x * x * x; } fn hidden() {}",
+	);
+
+	let mut instance = Instance::new();
+	instance.set_source_provider(provider);
+	instance.parse("import math; fn main() { return 0; }")?;
+
+	let warnings = instance
+		.get_warnings()
+		.iter()
+		.map(|x| x.get_message())
+		.collect::<Vec<_>>();
+
+	assert_eq!(warnings, ["function 'math::hidden' is never used"]);
+
+	let mut provider = MemoryProvider::new();
+	provider.add_module("math", "fn secret() { return 1; }");
+
+	let mut instance = Instance::new();
+	instance.set_source_provider(provider);
+
+	let error = instance
+		.parse("import math; fn main() { return math::secret(); }")
+		.unwrap_err()
+		.to_string();
+
+	assert!(error.contains("'math::secret' is private"), "{}", error);
+	assert!(
+		error.contains("= note: defined without 'pub' at math"),
+		"{}",
+		error
+	);
+
+	let mut provider = MemoryProvider::new();
+	provider.add_module("math", "pub fn one() { return 1; }");
+	provider.add_module("other", "import math; pub fn two() { return 2; }");
+
+	let mut instance = Instance::new();
+	instance.set_source_provider(provider);
+
+	let error = instance
+		.parse("import other; fn main() { return math::one() + other::two(); }")
+		.unwrap_err()
+		.to_string();
+
+	assert!(error.contains("without importing 'math'"), "{}", error);
+
+	let error = Instance::new()
+		.parse("import missing; fn main() { return 0; }")
+		.unwrap_err()
+		.to_string();
+
+	assert!(error.contains("cannot find module 'missing'"), "{}", error);
+	Ok(())
+}
+
+#[test]
+fn file_system_modules() -> Result<()> {
+	let root = std::env::temp_dir().join(format!("asdf-modules-{}", std::process::id()));
+
+	std::fs::create_dir_all(&root).unwrap();
+	std::fs::write(root.join("util.asdf"), "pub fn seven() { return 7; }").unwrap();
+
+	let mut instance = Instance::new();
+	instance.set_source_provider(FileSystemProvider::new(&root));
+
+	let ret = instance.parse("import util; fn main() { return util::seven(); }");
+	std::fs::remove_dir_all(&root).unwrap();
+
+	ret?;
+	assert_eq!(instance.execute(&[])?, 7);
+
+	Ok(())
+}
+
+#[test]
+fn check_and_dump() -> Result<()> {
+	let mut instance = Instance::new();
+	instance.add_source("main.asdf", "fn main() { return 1; }")?;
+	instance.check()?;
+
+	let mut instance = Instance::new();
+	instance.add_source("main.asdf", "fn main() { return one(); }")?;
+
+	let error = instance.check().unwrap_err().to_string();
+	assert!(error.contains("cannot find function 'one'"), "{}", error);
+
+	let mut instance = Instance::new();
+	instance.parse("fn main() { return 1; }")?;
+
+	let dump = instance.dump_hex();
+
+	// push rbp
+	assert!(dump.starts_with("main:\n\t00000000  55\n"), "{}", dump);
+	Ok(())
+}
+
+#[test]
+fn evaluate() -> Result<()> {
+	let mut instance = Instance::new();
+
+	instance.add_source("a", "fn double(x: i64) -> i64 { return x * 2; }")?;
+	instance.compile()?;
+
+	assert_eq!(instance.evaluate("double(21)")?, (42, Type::I64));
+
+	instance.add_source("b", "fn greet() -> str { return \"hi\"; }")?;
+	instance.compile()?;
+
+	let (value, typ) = instance.evaluate("greet() + \"!\"")?;
+
+	assert_eq!(typ, Type::Str);
+	assert_eq!(unsafe { (*(value as *const Str)).as_bytes() }, b"hi!");
+
+	assert_eq!(instance.evaluate("double(1) == 2")?, (1, Type::I64));
+	assert!(instance.evaluate("missing()").is_err());
+	assert_eq!(instance.evaluate("double(5)")?, (10, Type::I64));
+
+	let error = instance.execute(&[]).unwrap_err().to_string();
+	assert!(error.contains("cannot find 'main' function"), "{}", error);
+
+	// definitions and expressions are added on top of the compiled code, so what earlier ones
+	// returned stays valid
+	let (literal, _) = instance.evaluate("\"lit\"")?;
+
+	instance.extend("c", "fn same<T>(x: T) -> T { return x; }")?;
+	assert_eq!(instance.evaluate("same(double(4))")?, (8, Type::I64));
+	assert_eq!(instance.evaluate("same(\"a\").len()")?, (1, Type::I64));
+
+	assert!(instance
+		.extend("d", "fn broken() -> i64 { return \"x\"; }")
+		.is_err());
+	assert!(instance
+		.extend("e", "fn double(x: i64) -> i64 { return x; }")
+		.is_err());
+
+	let error = instance.evaluate("broken()").unwrap_err().to_string();
+	assert!(error.contains("cannot find function 'broken'"), "{}", error);
+
+	assert_eq!(instance.evaluate("double(same(3))")?, (6, Type::I64));
+
+	// only an expression is taken, and errors point into it
+	let error = instance
+		.evaluate("1; } fn evil() { return 2")
+		.unwrap_err()
+		.to_string();
+	assert!(
+		error.contains("found ';'\n --> <expression>:1:2"),
+		"{}",
+		error
+	);
+	assert!(instance.evaluate("evil()").is_err());
+
+	let error = instance.evaluate("double(\"x\")").unwrap_err().to_string();
+	assert!(error.contains(" --> <expression>:1:"), "{}", error);
+	assert_eq!(unsafe { (*(literal as *const Str)).as_bytes() }, b"lit");
+
+	Ok(())
+}
+
+#[test]
+fn main_arguments() -> Result<()> {
+	let mut instance = Instance::new();
+
+	instance.parse(
+		r#"
+
+		fn length(x: *char) -> i64 {
+			mut ret = 0;
+
+			while x[ret] != "\0"[0] {
+				ret = ret + 1;
+			}
+
+			return ret;
+		}
+
+		fn main(argc: i64, argv: **char) -> i64 {
+			mut ret = 0;
+			mut i = 0;
+
+			while i < argc {
+				ret = ret + length(argv[i]);
+				i = i + 1;
+			}
+
+			return ret * 10 + (argv[1][0] == "a"[0]);
+		}
+
+	"#,
+	)?;
+
+	assert_eq!(instance.execute(&["script", "ab", "cde"])?, 111);
+
+	// `*p` and `p[0]` are the same byte, and storing one leaves the ones after it alone
+	let mut instance = Instance::new();
+
+	instance.parse(
+		r#"
+
+		fn main(argc: i64, argv: **char) -> i64 {
+			mut p = argv[1];
+			mut ret = *p == p[0];
+
+			*p = "x"[0];
+			return ret + (p[0] == "x"[0]) * 10 + (p[1] == "b"[0]) * 100 + (p[2] == "\0"[0]) * 1000;
+		}
+
+	"#,
+	)?;
+
+	assert_eq!(instance.execute(&["script", "ab"])?, 1111);
+
+	extern "C" fn is_null(x: *const u8) -> i64 {
+		return x.is_null() as i64;
+	}
+
+	std::env::set_var("ASDF_TEST_VARIABLE", "1");
+
+	let mut instance = Instance::new();
+	instance.register_fn("is_null", is_null as extern "C" fn(_) -> _);
+
+	instance.parse(
+		r#"
+
+		fn starts_with(x: *char, prefix: str) -> i64 {
+			mut i = 0;
+
+			while i < prefix.len() {
+				if x[i] != prefix[i] {
+					return 0;
+				}
+
+				i = i + 1;
+			}
+
+			return 1;
+		}
+
+		fn main(argc: i64, argv: **char, envp: **char) -> i64 {
+			mut i = 0;
+
+			while is_null(envp[i]) == 0 {
+				if starts_with(envp[i], "ASDF_TEST_VARIABLE=1") {
+					return 1;
+				}
+
+				i = i + 1;
+			}
+
+			return 0;
+		}
+
+	"#,
+	)?;
+
+	assert_eq!(instance.execute(&[])?, 1);
+
+	let error = Instance::new()
+		.parse("fn main(x: str) { return 0; }")
+		.unwrap_err()
+		.to_string();
+
+	assert!(error.contains("'main' should have the type"), "{}", error);
+	Ok(())
+}
+
+#[test]
+fn stdlib() -> Result<()> {
+	let path = std::env::temp_dir().join(format!("asdf-stdlib-{}.txt", std::process::id()));
+	let path = path.to_str().unwrap();
+
+	let mut instance = Instance::new();
+
+	instance.parse(&format!(
+		r#"
+
+		fn main() {{
+			mut ret = 0;
+
+			if parse_int(" 42 ") == 42 && is_int("4x") == 0 {{
+				ret = ret + 1;
+			}}
+
+			if format2("{{}} + {{}}", to_string(1), "2") == "1 + 2" {{
+				ret = ret + 10;
+			}}
+
+			if abs(0 - 3) + min(4, 5) + max(6, 7) == 14 {{
+				ret = ret + 100;
+			}}
+
+			mut memory = malloc(16);
+			*memory = 5;
+
+			if *memory == 5 {{
+				ret = ret + 1000;
+			}}
+
+			free(memory);
+
+			mut file = open("{path}", "w");
+			write_file(file, "hello");
+			close(file);
+
+			file = open("{path}", "r");
+
+			if read_file(file) == "hello" {{
+				ret = ret + 10000;
+			}}
+
+			close(file);
+
+			if open("{path}/missing", "r") == 0 - 1 && clock() <= clock() && time() > 0 {{
+				ret = ret + 100000;
+			}}
+
+			return ret;
+		}}
+
+	"#
+	))?;
+
+	let ret = instance.execute(&[]);
+	std::fs::remove_file(path).unwrap();
+
+	assert_eq!(ret?, 111111);
+
+	// only the descriptors `open` handed out can be used, not the ones of the host
+	assert_eq!(
+		par(r#"
+
+		fn main() {
+			mut file = open("/nonexistent/file", "r");
+			mut ret = close(file) == 0 - 1;
+
+			ret = ret + (close(0) == 0 - 1) * 10;
+			ret = ret + (close(2) == 0 - 1) * 100;
+			ret = ret + (write_file(1, "x") == 0 - 1) * 1000;
+			ret = ret + (read_file(0) == "") * 10000;
+
+			file = open("/dev/null", "r");
+			ret = ret + (close(file) == 0) * 100000;
+			ret = ret + (close(file) == 0 - 1) * 1000000;
+
+			return ret;
+		}
+
+	"#)?,
+		1111111
+	);
+
+	let error = Instance::without_stdlib()
+		.parse("fn main() { print(\"hi\"); return 0; }")
+		.unwrap_err()
+		.to_string();
+
+	assert!(error.contains("cannot find function 'print'"), "{}", error);
+	Ok(())
+}
+
+#[test]
+fn diagnostics() -> Result<()> {
+	let error = Instance::new()
+		.parse(
+			r#"
+
+		fn add(a: i64, b: i64) -> i64 {
+			return a + b;
+		}
+
+		fn main() -> i64 {
+			missing();
+			add(1);
+			return "a";
+		}
+
+	"#,
+		)
+		.unwrap_err();
+
+	let codes = error.iter().map(|x| x.get_code()).collect::<Vec<_>>();
+	assert_eq!(codes, [Some("E0002"), Some("E0005"), Some("E0003")]);
+
+	let error = error.to_string();
+	assert!(
+		error.contains("error[E0005]: expected 2 arguments, got 1\n --> main.asdf:9:4"),
+		"{}",
+		error
+	);
+	assert!(
+		error.contains("= note: the function has the type"),
+		"{}",
+		error
+	);
+	assert!(
+		error.contains("= note: defined here at main.asdf:3:3"),
+		"{}",
+		error
+	);
+
+	// expressions point to the token they couldn't make sense of
+	let error = Instance::new()
+		.parse("fn main() {\n\treturn 1 + ;\n}")
+		.unwrap_err()
+		.to_string();
+
+	assert!(error.contains(" --> main.asdf:2:13\n"), "{}", error);
+	Ok(())
+}
+
+#[test]
+fn lints() -> Result<()> {
+	let mut instance = Instance::new();
+	instance.parse(
+		r#"
+
+		fn unused() -> i64 {
+			return 1;
+		}
+
+		#[allow(dead_code, unused_variables)]
+		fn allowed() -> i64 {
+			mut x = 1;
+			return 2;
+		}
+
+		impl i64 {
+			fn double(self: i64) -> i64 {
+				return self * 2;
+			}
+		}
+
+		fn main() -> i64 {
+			mut x = 1;
+			mut y = 2;
+			x = 3;
+
+			if x == 3 {
+				x = 4;
+			}
+
+			return x.double();
+			print("done");
+		}
+
+	"#,
+	)?;
+
+	let warnings = instance
+		.get_warnings()
+		.iter()
+		.map(|x| {
+			(
+				x.get_code().unwrap(),
+				x.get_primary().unwrap().get_line_number(),
+			)
+		})
+		.collect::<Vec<_>>();
+
+	assert_eq!(
+		warnings,
+		[("W0003", 29), ("W0002", 20), ("W0001", 21), ("W0004", 3)]
+	);
+
+	let error = Instance::new()
+		.parse("#[allow(everything)] fn main() { return 0; }")
+		.unwrap_err()
+		.to_string();
+
+	assert!(error.contains("unknown lint 'everything'"), "{}", error);
+	Ok(())
+}
+
+#[test]
+fn syntax_errors() -> Result<()> {
+	let error = Instance::new()
+		.parse(
+			r#"
+
+		fn first(x i64) -> i64 {
+			return x;
+		}
+
+		fn main() -> i64 {
+			mut a = 1 +;
+			return a b;
+		}
+
+		fn last() {
+			return 0;
+		}
+
+	"#,
+		)
+		.unwrap_err();
+
+	let messages = error.iter().map(|x| x.get_message()).collect::<Vec<_>>();
+
+	assert_eq!(
+		messages,
+		[
+			"expected ':', found 'i64'",
+			"expected a value for 'Addition', found ';'",
+			"expected ';', found 'b'",
+		]
+	);
+
+	Ok(())
+}
+
+#[test]
+fn nested_blocks() -> Result<()> {
+	let depth = 200;
+
+	let source = format!(
+		"fn add(a: i64, b: i64) -> i64 {{ return a + b; }}\n\
+		fn main() {{ mut ret = 0; {} ret = add(ret, ((1))); {} return ret; }}",
+		"if 1 { ".repeat(depth),
+		"} ".repeat(depth),
+	);
+
+	assert_eq!(par(&source)?, 1);
+
+	// an error deep inside of a block doesn't hide the ones after it
+	let error = Instance::new()
+		.parse("fn main() {\n\twhile 1 {\n\t\tif 1 { ret = ; }\n\t\t1 2;\n\t}\n\treturn 0\n}")
+		.unwrap_err();
+
+	let messages = error.iter().map(|x| x.get_message()).collect::<Vec<_>>();
+
+	assert_eq!(
+		messages,
+		[
+			"expected a value for 'Assignment', found ';'",
+			"expected ';', found '2'",
+			"expected ';', found '}'",
+		]
+	);
+
+	Ok(())
+}
+
+#[test]
+fn comments() -> Result<()> {
+	let mut instance = Instance::new();
+
+	instance.parse(
+		r#"
+/// adds one to the value
+///
+/// which is then returned
+fn increment(x: i64) -> i64 {
+	return x + 1; // trailing comment
+}
+
+/* a block comment /* with another one
+   in it */ that spans lines */
+fn main() {
+	return increment(/* 41 */ 1 // 2
+	);
+}
+"#,
+	)?;
+
+	assert_eq!(instance.execute(&[])?, 2);
+	assert_eq!(
+		instance.get_doc("increment"),
+		Some("adds one to the value\n\nwhich is then returned")
+	);
+	assert_eq!(instance.get_doc("main"), None);
+
+	// lines inside of comments are still counted
+	let error = Instance::new()
+		.parse("/*\n\n*/ // x\n//// y\nfn main() { return x; }")
+		.unwrap_err()
+		.to_string();
+
+	assert!(error.contains(" --> main.asdf:5:20\n"), "{}", error);
+
+	let error = Instance::new()
+		.parse("fn main() { return 0; }\n/* /* */")
+		.unwrap_err();
+
+	assert_eq!(
+		error.iter().map(|x| x.get_message()).collect::<Vec<_>>(),
+		["unterminated block comment"]
+	);
+
+	Ok(())
+}
+
+#[test]
+fn integer_literals() -> Result<()> {
+	assert_eq!(
+		par(r#"
+
+		fn main() {
+			return 0xff + 0o17 + 0b1010 + 1_000_000 + 0xFF_FF + 10i64;
+		}
+
+	"#)?,
+		255 + 15 + 10 + 1_000_000 + 0xffff + 10
+	);
+
+	assert_eq!(
+		par("fn main() { return 0x7fff_ffff_ffff_ffff; }")?,
+		i64::MAX
+	);
+	assert_eq!(par("fn main() { return 9223372036854775807; }")?, i64::MAX);
+
+	// the lexer takes the one suffix there's a type for
+	assert_eq!(crate::asdf::lex("10i64 0xffi64")?, 2);
+	assert!(crate::asdf::lex("10i32").is_err());
+
+	let messages = |source: &str| {
+		Instance::new()
+			.parse(source)
+			.unwrap_err()
+			.iter()
+			.map(|x| x.to_string().lines().take(2).collect::<Vec<_>>().join("\n"))
+			.collect::<Vec<_>>()
+	};
+
+	assert_eq!(
+		messages("fn main() {\n\treturn 99999999999999999999;\n}"),
+		["error[E0001]: integer literal is out of range for 'i64'\n --> main.asdf:2:9"]
+	);
+	assert_eq!(
+		messages("fn main() { return 0xffff_ffff_ffff_ffff; }"),
+		["error[E0001]: integer literal is out of range for 'i64'\n --> main.asdf:1:20"]
+	);
+	assert_eq!(
+		messages("fn main() { return 0b102; }"),
+		["error[E0001]: invalid digit '2' in a binary literal\n --> main.asdf:1:20"]
+	);
+	assert_eq!(
+		messages("fn main() { return 0x; }"),
+		["error[E0001]: expected digits after '0x'\n --> main.asdf:1:20"]
+	);
+	assert_eq!(
+		messages("fn main() { return 200u8; }"),
+		["error[E0001]: unsupported integer suffix 'u8', 'i64' is the only integer type\n --> main.asdf:1:20"]
+	);
+
+	Ok(())
+}
+
+#[test]
+fn no_panics() -> Result<()> {
+	// used to run into the next function, without a '->' it returns an 'i64'
+	assert_eq!(
+		par("fn nothing() {} fn main() { mut x = nothing(); return x + 1; }")?,
+		1
+	);
+
+	let messages = |source: &str| {
+		Instance::new()
+			.parse(source)
+			.unwrap_err()
+			.iter()
+			.map(|x| x.get_message().to_string())
+			.collect::<Vec<_>>()
+	};
+
+	// an assignment has no value to use
+	let error = Instance::new()
+		.parse("fn main() {\n\tmut x = 0;\n\tmut y = (x = 1);\n\treturn 0;\n}")
+		.unwrap_err()
+		.to_string();
+
+	assert!(
+		error.contains("'y' can't hold a value of type '()'\n --> main.asdf:3:2"),
+		"{}",
+		error
+	);
+	assert_eq!(
+		messages("fn main() { mut x = 0; return (x = 1) + 1; }"),
+		["expected '()', got 'i64'"]
+	);
+
+	// a generic instance can't take '()' either, it used to panic while emitting
+	let error = par("fn id<T>(x: T) -> T { return x; } fn main() { id(bp()); return 0; }")
+		.unwrap_err()
+		.to_string();
+
+	assert!(
+		error.contains("type parameter 'T' of 'id' can't be '()'"),
+		"{}",
+		error
+	);
+
+	let instance = Instance::new();
+	assert!(instance.execute(&[]).is_err());
+	assert!(instance.call::<(), i64>("main", ()).is_err());
+	assert_eq!(instance.dump_hex(), "");
+
+	let source = r#"
+		trait Shape { fn area(self: Self) -> i64; }
+		impl Shape for i64 { fn area(self: Self) -> i64 { return self * self; } }
+		fn first<T>(x: *T) -> T { return x[0]; }
+		/// doc
+		#[allow(unused_variables)]
+		fn main() {
+			mut s = "a\x41";
+			mut n = 0xff;
+			while n > 0 { if n % 2 == 0 { n = n / 2; } n = n - 1; }
+			return first(&n) + n.area() + (1 + 2) * 3;
+		}
+	"#;
+
+	// every prefix, and every single character left out, has to fail gracefully
+	let indices = source.char_indices().map(|(i, _)| i);
+
+	for i in indices.clone() {
+		let _ = Instance::new().parse(&source[..i]);
+	}
+
+	for i in indices {
+		let mut mutated = source.to_string();
+		mutated.remove(i);
+
+		let _ = Instance::new().parse(&mutated);
+	}
+
+	Ok(())
+}
+
+#[test]
+fn nesting_limit() -> Result<()> {
+	let parens = |depth: usize| {
+		format!(
+			"fn main() {{ return {}1{}; }}",
+			"(".repeat(depth),
+			")".repeat(depth)
+		)
+	};
+
+	let blocks = |depth: usize| {
+		format!(
+			"fn main() {{ mut x = 0; {} x = 1 + 2 * 3; {} return x; }}",
+			"if 1 { ".repeat(depth),
+			"} ".repeat(depth)
+		)
+	};
+
+	// the body of `main` and `return` take up a level each
+	assert_eq!(par(&parens(254))?, 1);
+	assert_eq!(par(&blocks(254))?, 7);
+
+	for source in [parens(255), blocks(256)] {
+		let error = Instance::new().parse(&source).unwrap_err();
+
+		assert_eq!(
+			error.iter().map(|x| x.get_message()).collect::<Vec<_>>(),
+			["nested more than 256 levels deep"]
+		);
+	}
+
+	// only parentheses and blocks nest, a chain of operators doesn't
+	let chain = |terms: usize| format!("fn main() {{ return {}1; }}", "1 + ".repeat(terms - 1));
+	assert_eq!(par(&chain(256))?, 256);
+
+	let error = Instance::new().parse(&chain(258)).unwrap_err();
+
+	assert_eq!(
+		error.iter().map(|x| x.get_message()).collect::<Vec<_>>(),
+		["more than 256 operators in one expression"]
+	);
+
+	assert_eq!(par("fn main() { return 10 - 3 - 2; }")?, 5);
+	assert_eq!(par("fn main() { return 1 + 7 % 4; }")?, 4);
+	assert_eq!(par("fn main() { return 2 * 7 % 4; }")?, 2);
+	assert_eq!(par("fn main() { return 100 / 10 / 5; }")?, 2);
+	assert_eq!(par("fn main() { return 2 * 3 + 4 * 5 - 6; }")?, 20);
+	assert_eq!(
+		par("fn main() { mut a = 0; a = 1 * 10 + 1; return a; }")?,
+		11
+	);
+
+	Ok(())
+}
+
+#[test]
+fn formatting() -> Result<()> {
+	let source = r#"
+/// squares
+///   anything
+fn square<T>( x:T )->T{return x*x;}
+
+
+trait Shape{fn area(self:Self)->i64;}
+impl Shape for i64 { fn area(self: Self) -> i64 { return self * self; } }
+
+#[allow(dead_code)] fn unused() {}
+
+fn deref(p:*i64)->i64{return *p;}
+
+fn main(){
+	mut x=0xff_ff; // trailing
+
+	/* nested /* block */ comment */
+	if x>=2&&(x==3)||x!=4 {
+		print(to_string(x));
+
+
+	}
+	return square(2)+math::abs(3)+x.area()+argv[0]
+		[1];
+}
+"#;
+
+	let expected = r#"/// squares
+///   anything
+fn square<T>(x: T) -> T {
+	return x * x;
+}
+
+trait Shape {
+	fn area(self: Self) -> i64;
+}
+impl Shape for i64 {
+	fn area(self: Self) -> i64 {
+		return self * self;
+	}
+}
+
+#[allow(dead_code)]
+fn unused() {}
+
+fn deref(p: *i64) -> i64 {
+	return *p;
+}
+
+fn main() {
+	mut x = 0xff_ff; // trailing
+
+	/* nested /* block */ comment */
+	if x >= 2 && (x == 3) || x != 4 {
+		print(to_string(x));
+	}
+	return square(2) + math::abs(3) + x.area() + argv[0][1];
+}
+"#;
+
+	let formatted = crate::asdf::format("test.asdf", source)?;
+	assert_eq!(formatted, expected);
+	assert_eq!(crate::asdf::format("test.asdf", &formatted)?, formatted);
+
+	let fizz_buzz = include_str!("../examples/fizz_buzz.asdf");
+	assert_eq!(crate::asdf::format("fizz_buzz.asdf", fizz_buzz)?, fizz_buzz);
+
+	// comments in the middle of a statement break it up, the rest of it is indented
+	let source = r#"
+fn main() {
+	mut x = 1 + // one
+	2;
+	mut y = 3 /* three */ * x;
+	print(to_string(x), // x
+	y);
+	return x +
+	// y
+	y;
+}
+"#;
+
+	let expected = r#"fn main() {
+	mut x = 1 + // one
+		2;
+	mut y = 3 /* three */ * x;
+	print(to_string(x), // x
+		y);
+	return x +
+		// y
+		y;
+}
+"#;
+
+	let formatted = crate::asdf::format("test.asdf", source)?;
+	assert_eq!(formatted, expected);
+	assert_eq!(crate::asdf::format("test.asdf", &formatted)?, formatted);
+
+	// only sources that parse are formatted
+	assert!(crate::asdf::format("test.asdf", "fn main() { return 1 +; }").is_err());
+
+	Ok(())
+}